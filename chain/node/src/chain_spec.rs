@@ -1,7 +1,8 @@
 use sp_core::{Pair, Public, sr25519};
 use node_archipel_runtime::{
-	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig,
-	SudoConfig, SystemConfig, WASM_BINARY, Signature
+	AccountId, ArchipelModuleConfig, AuraConfig, BalancesConfig, CouncilConfig, CouncilMembershipConfig,
+	GenesisConfig, GrandpaConfig, SessionConfig, SudoConfig, SystemConfig, ValidatorSetConfig, WASM_BINARY,
+	Signature, opaque::SessionKeys, pallet_archipel::crypto::Public as ArchipelId,
 };
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_finality_grandpa::AuthorityId as GrandpaId;
@@ -30,14 +31,20 @@ pub fn get_account_id_from_seed<TPublic: Public>(seed: &str) -> AccountId where
 	AccountPublic::from(get_from_seed::<TPublic>(seed)).into_account()
 }
 
-/// Generate an Aura authority key.
-pub fn authority_keys_from_seed(s: &str) -> (AuraId, GrandpaId) {
+/// Generate a validator account with its session keys.
+pub fn authority_keys_from_seed(s: &str) -> (AccountId, AuraId, GrandpaId, ArchipelId) {
 	(
+		get_account_id_from_seed::<sr25519::Public>(s),
 		get_from_seed::<AuraId>(s),
 		get_from_seed::<GrandpaId>(s),
+		get_from_seed::<ArchipelId>(s),
 	)
 }
 
+fn session_keys(aura: AuraId, grandpa: GrandpaId, archipel: ArchipelId) -> SessionKeys {
+	SessionKeys { aura, grandpa, archipel }
+}
+
 pub fn development_config() -> Result<ChainSpec, String> {
 	let wasm_binary = WASM_BINARY.ok_or_else(|| "Development wasm not available".to_string())?;
 
@@ -54,7 +61,11 @@ pub fn development_config() -> Result<ChainSpec, String> {
 				authority_keys_from_seed("Alice"),
 			],
 			// Sudo account
-			get_account_id_from_seed::<sr25519::Public>("Alice"),
+			Some(get_account_id_from_seed::<sr25519::Public>("Alice")),
+			// Federation council
+			vec![
+				get_account_id_from_seed::<sr25519::Public>("Alice"),
+			],
 			// Pre-funded accounts
 			vec![
 				get_account_id_from_seed::<sr25519::Public>("Alice"),
@@ -62,6 +73,15 @@ pub fn development_config() -> Result<ChainSpec, String> {
 				get_account_id_from_seed::<sr25519::Public>("Alice//stash"),
 				get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
 			],
+			// Archipel groups
+			vec![
+				(get_account_id_from_seed::<sr25519::Public>("Alice"), 1),
+				(get_account_id_from_seed::<sr25519::Public>("Bob"), 1),
+			],
+			// Archipel group leaders
+			vec![
+				(1, get_account_id_from_seed::<sr25519::Public>("Alice")),
+			],
 			true,
 		),
 		// Bootnodes
@@ -94,7 +114,13 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 				authority_keys_from_seed("Bob"),
 			],
 			// Sudo account
-			get_account_id_from_seed::<sr25519::Public>("Alice"),
+			Some(get_account_id_from_seed::<sr25519::Public>("Alice")),
+			// Federation council
+			vec![
+				get_account_id_from_seed::<sr25519::Public>("Alice"),
+				get_account_id_from_seed::<sr25519::Public>("Bob"),
+				get_account_id_from_seed::<sr25519::Public>("Charlie"),
+			],
 			// Pre-funded accounts
 			vec![
 				get_account_id_from_seed::<sr25519::Public>("Alice"),
@@ -110,6 +136,77 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 				get_account_id_from_seed::<sr25519::Public>("Eve//stash"),
 				get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
 			],
+			// Archipel groups
+			vec![
+				(get_account_id_from_seed::<sr25519::Public>("Alice"), 1),
+				(get_account_id_from_seed::<sr25519::Public>("Bob"), 1),
+				(get_account_id_from_seed::<sr25519::Public>("Charlie"), 1),
+				(get_account_id_from_seed::<sr25519::Public>("Dave"), 2),
+				(get_account_id_from_seed::<sr25519::Public>("Eve"), 2),
+				(get_account_id_from_seed::<sr25519::Public>("Ferdie"), 2),
+			],
+			// Archipel group leaders
+			vec![
+				(1, get_account_id_from_seed::<sr25519::Public>("Alice")),
+				(2, get_account_id_from_seed::<sr25519::Public>("Dave")),
+			],
+			true,
+		),
+		// Bootnodes
+		vec![],
+		// Telemetry
+		None,
+		// Protocol ID
+		None,
+		// Properties
+		None,
+		// Extensions
+		None,
+	))
+}
+
+/// Local federation governed by a council of three operators, without sudo key.
+pub fn federation_config() -> Result<ChainSpec, String> {
+	let wasm_binary = WASM_BINARY.ok_or_else(|| "Development wasm not available".to_string())?;
+
+	Ok(ChainSpec::from_genesis(
+		// Name
+		"Federation Testnet",
+		// ID
+		"federation",
+		ChainType::Local,
+		move || testnet_genesis(
+			wasm_binary,
+			// Initial PoA authorities
+			vec![
+				authority_keys_from_seed("Alice"),
+				authority_keys_from_seed("Bob"),
+				authority_keys_from_seed("Charlie"),
+			],
+			// No sudo account, root calls require council approval
+			None,
+			// Federation council
+			vec![
+				get_account_id_from_seed::<sr25519::Public>("Alice"),
+				get_account_id_from_seed::<sr25519::Public>("Bob"),
+				get_account_id_from_seed::<sr25519::Public>("Charlie"),
+			],
+			// Pre-funded accounts
+			vec![
+				get_account_id_from_seed::<sr25519::Public>("Alice"),
+				get_account_id_from_seed::<sr25519::Public>("Bob"),
+				get_account_id_from_seed::<sr25519::Public>("Charlie"),
+			],
+			// Archipel groups
+			vec![
+				(get_account_id_from_seed::<sr25519::Public>("Alice"), 1),
+				(get_account_id_from_seed::<sr25519::Public>("Bob"), 1),
+				(get_account_id_from_seed::<sr25519::Public>("Charlie"), 1),
+			],
+			// Archipel group leaders
+			vec![
+				(1, get_account_id_from_seed::<sr25519::Public>("Alice")),
+			],
 			true,
 		),
 		// Bootnodes
@@ -128,9 +225,12 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 /// Configure initial storage state for FRAME modules.
 fn testnet_genesis(
 	wasm_binary: &[u8],
-	initial_authorities: Vec<(AuraId, GrandpaId)>,
-	root_key: AccountId,
+	initial_authorities: Vec<(AccountId, AuraId, GrandpaId, ArchipelId)>,
+	root_key: Option<AccountId>,
+	council_members: Vec<AccountId>,
 	endowed_accounts: Vec<AccountId>,
+	archipel_groups: Vec<(AccountId, u32)>,
+	archipel_leaders: Vec<(u32, AccountId)>,
 	_enable_println: bool,
 ) -> GenesisConfig {
 	GenesisConfig {
@@ -143,15 +243,70 @@ fn testnet_genesis(
 			// Configure endowed accounts with initial balance of 1 << 60.
 			balances: endowed_accounts.iter().cloned().map(|k|(k, 1 << 60)).collect(),
 		},
+		pallet_validator_set: ValidatorSetConfig {
+			validators: initial_authorities.iter().map(|x| x.0.clone()).collect(),
+		},
+		pallet_session: SessionConfig {
+			keys: initial_authorities.iter().map(|x| {
+				(x.0.clone(), x.0.clone(), session_keys(x.1.clone(), x.2.clone(), x.3.clone()))
+			}).collect(),
+		},
+		// Aura and GRANDPA authorities are set by the first session.
 		pallet_aura: AuraConfig {
-			authorities: initial_authorities.iter().map(|x| (x.0.clone())).collect(),
+			authorities: vec![],
 		},
 		pallet_grandpa: GrandpaConfig {
-			authorities: initial_authorities.iter().map(|x| (x.1.clone(), 1)).collect(),
+			authorities: vec![],
 		},
 		pallet_sudo: SudoConfig {
-			// Assign network admin rights.
-			key: root_key,
+			// Assign network admin rights, the default account has no known private key.
+			key: root_key.unwrap_or_default(),
+		},
+		pallet_collective_Instance1: CouncilConfig {
+			// Council members are initialized by council membership.
+			members: vec![],
+			phantom: Default::default(),
+		},
+		pallet_membership_Instance1: CouncilMembershipConfig {
+			members: council_members,
+			phantom: Default::default(),
+		},
+		pallet_archipel: ArchipelModuleConfig {
+			// Endowed accounts are the initial federation members.
+			members: endowed_accounts,
+			groups: archipel_groups,
+			leaders: archipel_leaders,
 		},
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_runtime::codec::Decode;
+	use sp_runtime::BuildStorage;
+
+	// Sudo key set in the genesis storage of a chain spec
+	fn sudo_key(spec: ChainSpec) -> AccountId {
+		let storage = spec.build_storage().unwrap();
+		let key = [sp_core::twox_128(b"Sudo"), sp_core::twox_128(b"Key")].concat();
+		AccountId::decode(&mut &storage.top[&key][..]).unwrap()
+	}
+
+	#[test]
+	fn only_development_specs_should_have_sudo_key() {
+		let alice = get_account_id_from_seed::<sr25519::Public>("Alice");
+		assert_eq!(sudo_key(development_config().unwrap()), alice);
+		assert_eq!(sudo_key(local_testnet_config().unwrap()), alice);
+
+		// Federation sudo key is the default account nobody holds the private key of
+		assert_eq!(sudo_key(federation_config().unwrap()), AccountId::default());
+	}
+
+	#[test]
+	fn federation_spec_should_have_council() {
+		let storage = federation_config().unwrap().build_storage().unwrap();
+		let council_members = [sp_core::twox_128(b"Council"), sp_core::twox_128(b"Members")].concat();
+		assert!(storage.top.contains_key(&council_members));
+	}
+}
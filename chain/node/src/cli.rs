@@ -1,6 +1,28 @@
+use std::str::FromStr;
 use structopt::StructOpt;
 use sc_cli::RunCmd;
 
+/// Block authoring used in place of Aura and GRANDPA on development nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sealing {
+	/// Blocks are created and finalized on `engine_createBlock` and `engine_finalizeBlock` calls.
+	Manual,
+	/// A block is created and finalized as soon as a transaction enters the pool.
+	Instant,
+}
+
+impl FromStr for Sealing {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"manual" => Ok(Sealing::Manual),
+			"instant" => Ok(Sealing::Instant),
+			_ => Err(format!("Unknown sealing mode: {}", s)),
+		}
+	}
+}
+
 #[derive(Debug, StructOpt)]
 pub struct Cli {
 	#[structopt(subcommand)]
@@ -8,6 +30,10 @@ pub struct Cli {
 
 	#[structopt(flatten)]
 	pub run: RunCmd,
+
+	/// Seal blocks manually or instantly instead of running Aura and GRANDPA.
+	#[structopt(long, possible_values = &["manual", "instant"])]
+	pub sealing: Option<Sealing>,
 }
 
 #[derive(Debug, StructOpt)]
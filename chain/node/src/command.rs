@@ -50,6 +50,7 @@ impl SubstrateCli for Cli {
 		Ok(match id {
 			"dev" => Box::new(chain_spec::development_config()?),
 			"" | "local" => Box::new(chain_spec::local_testnet_config()?),
+			"federation" => Box::new(chain_spec::federation_config()?),
 			path => Box::new(chain_spec::ChainSpec::from_json_file(
 				std::path::PathBuf::from(path),
 			)?),
@@ -127,9 +128,15 @@ pub fn run() -> sc_cli::Result<()> {
 		},
 		None => {
 			let runner = cli.create_runner(&cli.run)?;
+			let sealing = cli.sealing;
 			runner.run_node_until_exit(|config| async move {
-				match config.role {
-					Role::Light => service::new_light(config),
+				match (config.role.clone(), sealing) {
+					// Light clients don't author blocks so they can't seal them either
+					(Role::Light, Some(_)) => return Err(sc_cli::Error::Input(
+						"`--sealing` can't be used with `--light`, sealing requires a full node".into()
+					)),
+					(Role::Light, None) => service::new_light(config),
+					(_, Some(sealing)) => service::new_manual_seal(config, sealing),
 					_ => service::new_full(config),
 				}.map_err(sc_cli::Error::Service)
 			})
@@ -0,0 +1,89 @@
+//! Helpers letting manual and instant seal author blocks accepted by the Aura runtime.
+//!
+//! `pallet_aura` expects each block to carry an Aura pre-runtime digest with a
+//! slot strictly greater than its parent and matching the block timestamp.
+//! Manually sealed blocks are therefore stamped one slot after each other,
+//! whatever the wall clock says.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use node_archipel_runtime::{opaque::Block, Hash};
+use sc_consensus_manual_seal::{ConsensusDataProvider, Error};
+use sp_consensus::BlockImportParams;
+use sp_consensus_aura::AURA_ENGINE_ID;
+use sp_inherents::{InherentData, InherentIdentifier, ProvideInherentData};
+use sp_runtime::{codec::Encode, generic::{Digest, DigestItem}, traits::Block as BlockT};
+
+/// Timestamp inherent moving forward by one slot at each sealed block.
+pub struct SlotTimestampProvider {
+	slot_duration: u64,
+	next: AtomicU64,
+}
+
+impl SlotTimestampProvider {
+	/// Starts at the slot following both `last` timestamp and the current time.
+	pub fn new(slot_duration: u64, last: u64) -> Self {
+		let now = SystemTime::now().duration_since(UNIX_EPOCH)
+			.map(|d| d.as_millis() as u64)
+			.unwrap_or_default();
+		let slot = last.max(now) / slot_duration + 1;
+		SlotTimestampProvider {
+			slot_duration,
+			next: AtomicU64::new(slot * slot_duration),
+		}
+	}
+}
+
+impl ProvideInherentData for SlotTimestampProvider {
+	fn inherent_identifier(&self) -> &'static InherentIdentifier {
+		&sp_timestamp::INHERENT_IDENTIFIER
+	}
+
+	fn provide_inherent_data(&self, inherent_data: &mut InherentData) -> Result<(), sp_inherents::Error> {
+		let timestamp = self.next.fetch_add(self.slot_duration, Ordering::SeqCst);
+		inherent_data.put_data(sp_timestamp::INHERENT_IDENTIFIER, &timestamp)
+	}
+
+	fn error_to_string(&self, error: &[u8]) -> Option<String> {
+		Some(String::from_utf8_lossy(error).into())
+	}
+}
+
+/// Adds the Aura slot of the block timestamp to manually sealed blocks.
+pub struct AuraDigestProvider<Transaction> {
+	slot_duration: u64,
+	_phantom: std::marker::PhantomData<fn() -> Transaction>,
+}
+
+impl<Transaction> AuraDigestProvider<Transaction> {
+	/// Creates a provider for the given slot duration in milliseconds.
+	pub fn new(slot_duration: u64) -> Self {
+		AuraDigestProvider { slot_duration, _phantom: Default::default() }
+	}
+}
+
+impl<Transaction: Send + 'static> ConsensusDataProvider<Block> for AuraDigestProvider<Transaction> {
+	type Transaction = Transaction;
+
+	fn create_digest(
+		&self,
+		_parent: &<Block as BlockT>::Header,
+		inherents: &InherentData,
+	) -> Result<Digest<Hash>, Error> {
+		let timestamp: u64 = inherents.get_data(&sp_timestamp::INHERENT_IDENTIFIER)
+			.map_err(|e| Error::StringError(format!("{:?}", e)))?
+			.ok_or_else(|| Error::StringError("No timestamp in inherent data".into()))?;
+		let slot = timestamp / self.slot_duration;
+
+		Ok(Digest { logs: vec![DigestItem::PreRuntime(AURA_ENGINE_ID, slot.encode())] })
+	}
+
+	fn append_block_import(
+		&self,
+		_parent: &<Block as BlockT>::Header,
+		_params: &mut BlockImportParams<Block, Self::Transaction>,
+		_inherents: &InherentData,
+	) -> Result<(), Error> {
+		Ok(())
+	}
+}
@@ -7,10 +7,14 @@
 
 use std::sync::Arc;
 
-use node_archipel_runtime::{opaque::Block, AccountId, Balance, Index};
+use futures::channel::mpsc::Sender;
+use node_archipel_runtime::{opaque::Block, AccountId, Balance, BlockNumber, Hash, Index};
 use sp_api::ProvideRuntimeApi;
 use sp_blockchain::{Error as BlockChainError, HeaderMetadata, HeaderBackend};
 use sp_block_builder::BlockBuilder;
+use sc_client_api::{BlockBackend, BlockchainEvents, ProofProvider};
+use sc_consensus_manual_seal::EngineCommand;
+use sc_finality_grandpa::GrandpaJustificationStream;
 pub use sc_rpc_api::DenyUnsafe;
 use sp_transaction_pool::TransactionPool;
 
@@ -23,6 +27,12 @@ pub struct FullDeps<C, P> {
 	pub pool: Arc<P>,
 	/// Whether to deny unsafe calls
 	pub deny_unsafe: DenyUnsafe,
+	/// Executor used to spawn subscriptions.
+	pub subscription_executor: sc_rpc::SubscriptionTaskExecutor,
+	/// Stream of GRANDPA justifications of finalized blocks.
+	pub justification_stream: GrandpaJustificationStream<Block>,
+	/// Sink of manual seal commands, set when the node runs without Aura and GRANDPA.
+	pub command_sink: Option<Sender<EngineCommand<Hash>>>,
 }
 
 /// Instantiate all full RPC extensions.
@@ -31,20 +41,27 @@ pub fn create_full<C, P>(
 ) -> jsonrpc_core::IoHandler<sc_rpc::Metadata> where
 	C: ProvideRuntimeApi<Block>,
 	C: HeaderBackend<Block> + HeaderMetadata<Block, Error=BlockChainError> + 'static,
+	C: BlockchainEvents<Block> + BlockBackend<Block> + ProofProvider<Block>,
 	C: Send + Sync + 'static,
 	C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Index>,
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
+	C::Api: pallet_archipel_rpc::ArchipelRuntimeApi<Block, AccountId, BlockNumber>,
 	C::Api: BlockBuilder<Block>,
 	P: TransactionPool + 'static,
 {
 	use substrate_frame_rpc_system::{FullSystem, SystemApi};
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
+	use pallet_archipel_rpc::{Archipel, ArchipelApi};
+	use sc_consensus_manual_seal::rpc::{ManualSeal, ManualSealApi};
 
 	let mut io = jsonrpc_core::IoHandler::default();
 	let FullDeps {
 		client,
 		pool,
 		deny_unsafe,
+		subscription_executor,
+		justification_stream,
+		command_sink,
 	} = deps;
 
 	io.extend_with(
@@ -55,6 +72,17 @@ pub fn create_full<C, P>(
 		TransactionPaymentApi::to_delegate(TransactionPayment::new(client.clone()))
 	);
 
+	io.extend_with(
+		ArchipelApi::to_delegate(Archipel::new(client.clone(), subscription_executor, justification_stream))
+	);
+
+	if let Some(command_sink) = command_sink {
+		io.extend_with(
+			// Exposes `engine_createBlock` and `engine_finalizeBlock` to step blocks from tests.
+			ManualSealApi::to_delegate(ManualSeal::new(command_sink))
+		);
+	}
+
 	// Extend this RPC with a custom API by using the following syntax.
 	// `YourRpcStruct` should have a reference to a client, which is needed
 	// to call into the runtime.
@@ -2,8 +2,9 @@
 
 use std::sync::Arc;
 use std::time::Duration;
-use sc_client_api::{ExecutorProvider, RemoteBackend};
-use node_archipel_runtime::{self, opaque::Block, RuntimeApi};
+use futures::{channel::mpsc, stream::{self, Stream, StreamExt}};
+use sc_client_api::{ExecutorProvider, RemoteBackend, StorageProvider};
+use node_archipel_runtime::{self, opaque::Block, Hash, RuntimeApi};
 use sc_service::{error::Error as ServiceError, Configuration, TaskManager};
 use sp_inherents::InherentDataProviders;
 use sc_executor::native_executor_instance;
@@ -13,6 +14,12 @@ use sc_consensus_aura::{ImportQueueParams, StartAuraParams, SlotProportion};
 use sc_finality_grandpa::SharedVoterState;
 use sc_keystore::LocalKeystore;
 use sc_telemetry::{Telemetry, TelemetryWorker};
+use sc_consensus_manual_seal::EngineCommand;
+use sp_blockchain::HeaderBackend;
+use sp_core::{hashing::twox_128, storage::StorageKey};
+use sp_runtime::{codec::Decode, generic::BlockId};
+use crate::cli::Sealing;
+use crate::manual_seal::{AuraDigestProvider, SlotTimestampProvider};
 
 // Our native executor instance.
 native_executor_instance!(
@@ -178,12 +185,16 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 	let rpc_extensions_builder = {
 		let client = client.clone();
 		let pool = transaction_pool.clone();
+		let justification_stream = grandpa_link.justification_stream();
 
-		Box::new(move |deny_unsafe, _| {
+		Box::new(move |deny_unsafe, subscription_executor| {
 			let deps = crate::rpc::FullDeps {
 				client: client.clone(),
 				pool: pool.clone(),
 				deny_unsafe,
+				subscription_executor,
+				justification_stream: justification_stream.clone(),
+				command_sink: None,
 			};
 
 			crate::rpc::create_full(deps)
@@ -291,6 +302,165 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 	Ok(task_manager)
 }
 
+/// Builds a new development service sealing blocks on demand instead of running Aura and GRANDPA.
+pub fn new_manual_seal(config: Configuration, sealing: Sealing) -> Result<TaskManager, ServiceError> {
+	let telemetry = config.telemetry_endpoints.clone()
+		.filter(|x| !x.is_empty())
+		.map(|endpoints| -> Result<_, sc_telemetry::Error> {
+			let worker = TelemetryWorker::new(16)?;
+			let telemetry = worker.handle().new_telemetry(endpoints);
+			Ok((worker, telemetry))
+		})
+		.transpose()?;
+
+	let (client, backend, keystore_container, mut task_manager) =
+		sc_service::new_full_parts::<Block, RuntimeApi, Executor>(
+			&config,
+			telemetry.as_ref().map(|(_, telemetry)| telemetry.handle()),
+		)?;
+	let client = Arc::new(client);
+
+	let mut telemetry = telemetry
+		.map(|(worker, telemetry)| {
+			task_manager.spawn_handle().spawn("telemetry", worker.run());
+			telemetry
+		});
+
+	let select_chain = sc_consensus::LongestChain::new(backend.clone());
+
+	let transaction_pool = sc_transaction_pool::BasicPool::new_full(
+		config.transaction_pool.clone(),
+		config.role.is_authority().into(),
+		config.prometheus_registry(),
+		task_manager.spawn_handle(),
+		client.clone(),
+	);
+
+	let import_queue = sc_consensus_manual_seal::import_queue(
+		Box::new(client.clone()),
+		&task_manager.spawn_essential_handle(),
+		config.prometheus_registry(),
+	);
+
+	let (network, network_status_sinks, system_rpc_tx, network_starter) =
+		sc_service::build_network(sc_service::BuildNetworkParams {
+			config: &config,
+			client: client.clone(),
+			transaction_pool: transaction_pool.clone(),
+			spawn_handle: task_manager.spawn_handle(),
+			import_queue,
+			on_demand: None,
+			block_announce_validator_builder: None,
+		})?;
+
+	if config.offchain_worker.enabled {
+		sc_service::build_offchain_workers(
+			&config, task_manager.spawn_handle(), client.clone(), network.clone(),
+		);
+	}
+
+	let prometheus_registry = config.prometheus_registry().cloned();
+
+	// Blocks are sealed on `engine_*` rpc calls in both modes, instant sealing
+	// additionally seals and finalizes a block for each imported transaction.
+	let (command_sink, rpc_commands) = mpsc::channel(1024);
+	let commands_stream: Box<dyn Stream<Item = EngineCommand<Hash>> + Send + Unpin> = match sealing {
+		Sealing::Manual => Box::new(rpc_commands),
+		Sealing::Instant => Box::new(stream::select(
+			rpc_commands,
+			transaction_pool.pool().validated_pool().import_notification_stream()
+				.map(|_| EngineCommand::SealNewBlock {
+					create_empty: false,
+					finalize: true,
+					parent_hash: None,
+					sender: None,
+				}),
+		)),
+	};
+
+	let rpc_extensions_builder = {
+		let client = client.clone();
+		let pool = transaction_pool.clone();
+		// No GRANDPA voter runs, so the archipel rpc never receives justifications.
+		let (_, justification_stream) = sc_finality_grandpa::GrandpaJustificationStream::channel();
+
+		Box::new(move |deny_unsafe, subscription_executor| {
+			let deps = crate::rpc::FullDeps {
+				client: client.clone(),
+				pool: pool.clone(),
+				deny_unsafe,
+				subscription_executor,
+				justification_stream: justification_stream.clone(),
+				command_sink: Some(command_sink.clone()),
+			};
+
+			crate::rpc::create_full(deps)
+		})
+	};
+
+	sc_service::spawn_tasks(
+		sc_service::SpawnTasksParams {
+			network,
+			client: client.clone(),
+			keystore: keystore_container.sync_keystore(),
+			task_manager: &mut task_manager,
+			transaction_pool: transaction_pool.clone(),
+			rpc_extensions_builder,
+			on_demand: None,
+			remote_blockchain: None,
+			backend,
+			network_status_sinks,
+			system_rpc_tx,
+			config,
+			telemetry: telemetry.as_mut(),
+		},
+	)?;
+
+	let proposer_factory = sc_basic_authorship::ProposerFactory::new(
+		task_manager.spawn_handle(),
+		client.clone(),
+		transaction_pool.clone(),
+		prometheus_registry.as_ref(),
+		telemetry.as_ref().map(|x| x.handle()),
+	);
+
+	let slot_duration = node_archipel_runtime::SLOT_DURATION;
+	let inherent_data_providers = InherentDataProviders::new();
+	inherent_data_providers
+		.register_provider(SlotTimestampProvider::new(slot_duration, best_timestamp(&client)))
+		.map_err(|e| ServiceError::Other(format!("{:?}", e)))?;
+
+	let manual_seal = sc_consensus_manual_seal::run_manual_seal(
+		sc_consensus_manual_seal::ManualSealParams {
+			block_import: client.clone(),
+			env: proposer_factory,
+			client,
+			pool: transaction_pool.pool().clone(),
+			commands_stream,
+			select_chain,
+			consensus_data_provider: Some(Box::new(AuraDigestProvider::new(slot_duration))),
+			inherent_data_providers,
+		},
+	);
+
+	// the sealing task is considered essential, i.e. if it
+	// fails we take down the service with it.
+	task_manager.spawn_essential_handle().spawn_blocking("manual-seal", manual_seal);
+
+	network_starter.start_network();
+	Ok(task_manager)
+}
+
+/// Timestamp of the best block, sealing must not go back in time after a restart.
+fn best_timestamp(client: &FullClient) -> u64 {
+	let key = StorageKey([twox_128(b"Timestamp"), twox_128(b"Now")].concat());
+	client.storage(&BlockId::Hash(client.info().best_hash), &key)
+		.ok()
+		.flatten()
+		.and_then(|data| u64::decode(&mut &data.0[..]).ok())
+		.unwrap_or_default()
+}
+
 /// Builds a new service for a light client.
 pub fn new_light(mut config: Configuration) -> Result<TaskManager, ServiceError> {
 	let telemetry = config.telemetry_endpoints.clone()
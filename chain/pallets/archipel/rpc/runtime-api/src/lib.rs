@@ -0,0 +1,46 @@
+//! Runtime API definition for pallet-archipel.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+
+pub use pallet_archipel::{
+	ActionReason, GroupEvent, GroupInfo, GroupMemberState, GroupState, Leadership, Recommendation, RecommendedAction,
+	ServiceType,
+};
+use sp_std::prelude::*;
+
+sp_api::decl_runtime_apis! {
+	pub trait ArchipelApi<AccountId, BlockNumber> where
+		AccountId: Codec,
+		BlockNumber: Codec,
+	{
+		/// Leadership and members liveness of a group read at one block.
+		fn group_state(group_id: u32) -> GroupState<AccountId, BlockNumber>;
+
+		/// Leader, epoch and lease of a group read at one block.
+		fn leadership(group_id: u32) -> Leadership<AccountId, BlockNumber>;
+
+		/// Storage keys of the leader, epoch and leaded flag of a group.
+		fn leadership_keys(group_id: u32) -> Vec<Vec<u8>>;
+
+		/// Action a node should take for its service in a group, with its reason.
+		fn recommended_action(account: AccountId, group_id: u32) -> Recommendation<AccountId>;
+
+		/// Archipel events of a group deposited in the block.
+		fn group_events(group_id: u32) -> Vec<GroupEvent<AccountId, BlockNumber>>;
+
+		/// Members of a group with their liveness, at most `limit` members following `starting_after`.
+		fn group_members(
+			group_id: u32,
+			starting_after: Option<AccountId>,
+			limit: u32,
+		) -> Vec<GroupMemberState<AccountId, BlockNumber>>;
+
+		/// Groups a node sent heartbeats for, at most `limit` groups following `starting_after`.
+		fn node_groups(account: AccountId, starting_after: Option<u32>, limit: u32) -> Vec<u32>;
+
+		/// Registered groups, at most `limit` groups following `starting_after`.
+		fn registered_groups(starting_after: Option<u32>, limit: u32) -> Vec<(u32, GroupInfo<AccountId, BlockNumber, Vec<u8>>)>;
+	}
+}
@@ -0,0 +1,485 @@
+//! RPC interface for pallet-archipel.
+
+use std::sync::Arc;
+
+use codec::{Codec, Decode, Encode};
+use futures::{future, task::SpawnExt, FutureExt, SinkExt, StreamExt};
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use jsonrpc_pubsub::{manager::SubscriptionManager, typed::Subscriber, SubscriptionId};
+use log::warn;
+use parking_lot::RwLock;
+use sc_client_api::{BlockBackend, BlockchainEvents, ProofProvider};
+use sc_finality_grandpa::GrandpaJustificationStream;
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_finality_grandpa::GRANDPA_ENGINE_ID;
+use sp_runtime::{generic::BlockId, traits::{Block as BlockT, Header as HeaderT, NumberFor, Zero}};
+
+pub use archipel_verifier::{GrandpaJustification, LeadershipProof};
+
+pub use pallet_archipel_rpc_runtime_api::{
+	ActionReason, ArchipelApi as ArchipelRuntimeApi, GroupEvent, GroupInfo, GroupMemberState, GroupState, Leadership,
+	Recommendation, RecommendedAction, ServiceType,
+};
+
+/// Maximum number of entries returned by paginated methods.
+pub const MAX_PAGE_SIZE: u32 = 256;
+
+/// Maximum number of finalized blocks walked back to find a stored GRANDPA justification.
+///
+/// Nodes only store justifications of authority set changes and of one block every
+/// GRANDPA justification period (512 blocks by default).
+pub const MAX_JUSTIFICATION_LOOKBACK: u32 = 1024;
+
+/// Block a group events notification was read at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NotificationOrigin {
+	/// New best block, events may still be reverted.
+	Best,
+	/// Finalized block, leader changes are final.
+	Finalized,
+}
+
+/// Archipel events of a group deposited in a block.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupEventsNotification<Hash, AccountId, BlockNumber> {
+	/// Hash of the block events were deposited in.
+	pub block_hash: Hash,
+	/// Whether the block is a new best block or a finalized block.
+	pub origin: NotificationOrigin,
+	/// Group events, only leader changes are sent for finalized blocks.
+	pub events: Vec<GroupEvent<AccountId, BlockNumber>>,
+}
+
+/// Leadership of a group at best block and at finalized block.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeadershipView<Hash, AccountId, BlockNumber> {
+	/// Hash of the best block.
+	pub best_hash: Hash,
+	/// Leadership at best block, may still be reverted.
+	pub best: Leadership<AccountId, BlockNumber>,
+	/// Hash of the last finalized block.
+	pub finalized_hash: Hash,
+	/// Leadership at last finalized block.
+	pub finalized: Leadership<AccountId, BlockNumber>,
+	/// Best block leader and epoch are finalized.
+	pub is_final: bool,
+}
+
+/// Leadership storage proof at a block finalized by GRANDPA.
+///
+/// Checked by `archipel_verifier::verify_leadership` once converted into a `LeadershipProof`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeadershipProofResponse<Header> {
+	/// Header of the finalized block.
+	pub header: Header,
+	/// Encoded GRANDPA justification of the block.
+	pub justification: Bytes,
+	/// Trie nodes proving leadership storage values against the block state root.
+	pub proof: Vec<Bytes>,
+}
+
+impl<Header> From<LeadershipProofResponse<Header>> for LeadershipProof<Header> {
+	fn from(response: LeadershipProofResponse<Header>) -> Self {
+		LeadershipProof {
+			header: response.header,
+			justification: response.justification.to_vec(),
+			proof: response.proof.into_iter().map(|node| node.to_vec()).collect(),
+		}
+	}
+}
+
+/// Archipel RPC methods.
+#[rpc]
+pub trait ArchipelApi<BlockHash, Header, AccountId, BlockNumber> {
+	/// RPC metadata
+	type Metadata;
+
+	/// Leadership and members liveness of a group read at one block.
+	#[rpc(name = "archipel_groupState")]
+	fn group_state(&self, group_id: u32, at: Option<BlockHash>) -> Result<GroupState<AccountId, BlockNumber>>;
+
+	/// Leadership of a group at best and finalized blocks.
+	#[rpc(name = "archipel_leadership")]
+	fn leadership(&self, group_id: u32) -> Result<LeadershipView<BlockHash, AccountId, BlockNumber>>;
+
+	/// Proof of a group leadership at the last block finalized with a known GRANDPA justification.
+	///
+	/// Until a justification is broadcast, the proof is taken at the latest block with a stored
+	/// justification, which can be far behind the finalized head.
+	#[rpc(name = "archipel_leadershipProof")]
+	fn leadership_proof(&self, group_id: u32) -> Result<LeadershipProofResponse<Header>>;
+
+	/// Action a node should take for its service in a group, with its reason.
+	#[rpc(name = "archipel_recommendedAction")]
+	fn recommended_action(
+		&self,
+		account: AccountId,
+		group_id: u32,
+		at: Option<BlockHash>,
+	) -> Result<Recommendation<AccountId>>;
+
+	/// Members of a group with their liveness, at most `limit` members following `starting_after`.
+	#[rpc(name = "archipel_groupMembers")]
+	fn group_members(
+		&self,
+		group_id: u32,
+		starting_after: Option<AccountId>,
+		limit: u32,
+		at: Option<BlockHash>,
+	) -> Result<Vec<GroupMemberState<AccountId, BlockNumber>>>;
+
+	/// Groups a node sent heartbeats for, at most `limit` groups following `starting_after`.
+	#[rpc(name = "archipel_nodeGroups")]
+	fn node_groups(
+		&self,
+		account: AccountId,
+		starting_after: Option<u32>,
+		limit: u32,
+		at: Option<BlockHash>,
+	) -> Result<Vec<u32>>;
+
+	/// Registered groups, at most `limit` groups following `starting_after`.
+	#[rpc(name = "archipel_registeredGroups")]
+	fn registered_groups(
+		&self,
+		starting_after: Option<u32>,
+		limit: u32,
+		at: Option<BlockHash>,
+	) -> Result<Vec<(u32, GroupInfo<AccountId, BlockNumber, Vec<u8>>)>>;
+
+	/// Subscribe to heartbeats and leader changes of a group.
+	#[pubsub(
+		subscription = "archipel_groupEvents",
+		subscribe,
+		name = "archipel_subscribeGroupEvents"
+	)]
+	fn subscribe_group_events(
+		&self,
+		metadata: Self::Metadata,
+		subscriber: Subscriber<GroupEventsNotification<BlockHash, AccountId, BlockNumber>>,
+		group_id: u32,
+	);
+
+	/// Unsubscribe from group events.
+	#[pubsub(
+		subscription = "archipel_groupEvents",
+		unsubscribe,
+		name = "archipel_unsubscribeGroupEvents"
+	)]
+	fn unsubscribe_group_events(
+		&self,
+		metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> jsonrpc_core::Result<bool>;
+}
+
+/// Implementation of Archipel RPC methods.
+pub struct Archipel<C, Block: BlockT> {
+	client: Arc<C>,
+	manager: SubscriptionManager,
+	latest_justification: Arc<RwLock<Option<(Block::Hash, Vec<u8>)>>>,
+}
+
+impl<C, Block: BlockT> Archipel<C, Block> {
+	/// Create new `Archipel` with the given reference to the client, subscriptions executor
+	/// and GRANDPA justifications stream.
+	pub fn new(
+		client: Arc<C>,
+		executor: sc_rpc::SubscriptionTaskExecutor,
+		justification_stream: GrandpaJustificationStream<Block>,
+	) -> Self {
+		let latest_justification = Arc::new(RwLock::new(None));
+
+		// Justifications are only stored once in a while, keeping the last one broadcast
+		let latest = latest_justification.clone();
+		let keep_latest = justification_stream.subscribe().for_each(move |justification| {
+			let encoded = justification.encode();
+			match GrandpaJustification::<Block::Header>::decode(&mut &encoded[..]) {
+				Ok(decoded) => *latest.write() = Some((decoded.commit.target_hash, encoded)),
+				Err(e) => warn!("Unable to decode GRANDPA justification: {:?}", e),
+			}
+			future::ready(())
+		});
+		if let Err(e) = executor.spawn(keep_latest) {
+			warn!("Unable to follow GRANDPA justifications: {:?}", e);
+		}
+
+		Self {
+			client,
+			manager: SubscriptionManager::new(Arc::new(executor)),
+			latest_justification,
+		}
+	}
+}
+
+/// Error type of this RPC api.
+pub enum Error {
+	/// The call to runtime failed.
+	RuntimeError,
+	/// The leadership proof can't be built.
+	ProofError,
+}
+
+impl From<Error> for i64 {
+	fn from(e: Error) -> i64 {
+		match e {
+			Error::RuntimeError => 1,
+			Error::ProofError => 2,
+		}
+	}
+}
+
+// Converting runtime api errors into RPC errors
+fn runtime_error<E: std::fmt::Debug>(message: &str, error: E) -> RpcError {
+	RpcError {
+		code: ErrorCode::ServerError(Error::RuntimeError.into()),
+		message: message.into(),
+		data: Some(format!("{:?}", error).into()),
+	}
+}
+
+// Hashes of the blocks after block `last` up to `head`, in ascending order
+fn blocks_since<Block: BlockT, C: HeaderBackend<Block>>(
+	client: &C,
+	last: NumberFor<Block>,
+	head: Block::Hash,
+) -> Vec<Block::Hash> {
+	let mut hashes = Vec::new();
+	let mut hash = head;
+	while let Ok(Some(header)) = client.header(BlockId::hash(hash)) {
+		if *header.number() <= last {
+			break;
+		}
+		hashes.push(hash);
+		hash = *header.parent_hash();
+	}
+	hashes.reverse();
+	hashes
+}
+
+// Latest finalized block with a stored GRANDPA justification, with the encoded justification
+fn stored_justification<Block: BlockT, C: HeaderBackend<Block> + BlockBackend<Block>>(
+	client: &C,
+) -> Result<(Block::Hash, Vec<u8>)> {
+	let finalized_hash = client.info().finalized_hash;
+	let mut hash = finalized_hash;
+	for _ in 0..=MAX_JUSTIFICATION_LOOKBACK {
+		let justification = client.justifications(&BlockId::hash(hash))
+			.map_err(|e| proof_error("Unable to read justifications.", e))?
+			.and_then(|justifications| justifications.into_justification(GRANDPA_ENGINE_ID));
+		if let Some(justification) = justification {
+			return Ok((hash, justification));
+		}
+
+		let header = client.header(BlockId::hash(hash))
+			.map_err(|e| proof_error("Unable to read finalized header.", e))?
+			.ok_or_else(|| proof_error("Unknown finalized block.", hash))?;
+		if header.number().is_zero() {
+			break;
+		}
+		hash = *header.parent_hash();
+	}
+
+	Err(proof_error("No GRANDPA justification known yet.", finalized_hash))
+}
+
+// Converting client errors into RPC errors
+fn proof_error<E: std::fmt::Debug>(message: &str, error: E) -> RpcError {
+	RpcError {
+		code: ErrorCode::ServerError(Error::ProofError.into()),
+		message: message.into(),
+		data: Some(format!("{:?}", error).into()),
+	}
+}
+
+impl<C, Block, AccountId, BlockNumber>
+	ArchipelApi<<Block as BlockT>::Hash, <Block as BlockT>::Header, AccountId, BlockNumber> for Archipel<C, Block>
+where
+	Block: BlockT,
+	C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block> + BlockchainEvents<Block>,
+	C: BlockBackend<Block> + ProofProvider<Block>,
+	C::Api: ArchipelRuntimeApi<Block, AccountId, BlockNumber>,
+	AccountId: Codec + PartialEq + Serialize + Send + 'static,
+	BlockNumber: Codec + Serialize + Send + 'static,
+{
+	type Metadata = sc_rpc::Metadata;
+
+	fn group_state(
+		&self,
+		group_id: u32,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<GroupState<AccountId, BlockNumber>> {
+		let api = self.client.runtime_api();
+		// Using best block if no block hash is given
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.group_state(&at, group_id).map_err(|e| runtime_error("Unable to query group state.", e))
+	}
+
+	fn leadership(&self, group_id: u32) -> Result<LeadershipView<<Block as BlockT>::Hash, AccountId, BlockNumber>> {
+		let api = self.client.runtime_api();
+		let info = self.client.info();
+
+		let best = api.leadership(&BlockId::hash(info.best_hash), group_id)
+			.map_err(|e| runtime_error("Unable to query best block leadership.", e))?;
+		let finalized = api.leadership(&BlockId::hash(info.finalized_hash), group_id)
+			.map_err(|e| runtime_error("Unable to query finalized leadership.", e))?;
+
+		// Leadership can't be reverted anymore once its epoch is finalized
+		let is_final = best.leader == finalized.leader && best.epoch == finalized.epoch;
+
+		Ok(LeadershipView {
+			best_hash: info.best_hash,
+			best,
+			finalized_hash: info.finalized_hash,
+			finalized,
+			is_final,
+		})
+	}
+
+	fn leadership_proof(&self, group_id: u32) -> Result<LeadershipProofResponse<<Block as BlockT>::Header>> {
+		// Falling back to the latest justification stored by the node until one is broadcast
+		let latest_justification = self.latest_justification.read().clone();
+		let (hash, justification) = match latest_justification {
+			Some(latest) => latest,
+			None => stored_justification(&*self.client)?,
+		};
+
+		let at = BlockId::hash(hash);
+		let header = self.client.header(at)
+			.map_err(|e| proof_error("Unable to read finalized header.", e))?
+			.ok_or_else(|| proof_error("Unknown finalized block.", hash))?;
+		let keys = self.client.runtime_api().leadership_keys(&at, group_id)
+			.map_err(|e| runtime_error("Unable to query leadership keys.", e))?;
+		let proof = self.client.read_proof(&at, &mut keys.iter().map(|key| key.as_slice()))
+			.map_err(|e| proof_error("Unable to build leadership read proof.", e))?;
+
+		Ok(LeadershipProofResponse {
+			header,
+			justification: justification.into(),
+			proof: proof.iter_nodes().map(Into::into).collect(),
+		})
+	}
+
+	fn recommended_action(
+		&self,
+		account: AccountId,
+		group_id: u32,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Recommendation<AccountId>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.recommended_action(&at, account, group_id)
+			.map_err(|e| runtime_error("Unable to query recommended action.", e))
+	}
+
+	fn group_members(
+		&self,
+		group_id: u32,
+		starting_after: Option<AccountId>,
+		limit: u32,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Vec<GroupMemberState<AccountId, BlockNumber>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.group_members(&at, group_id, starting_after, limit.min(MAX_PAGE_SIZE))
+			.map_err(|e| runtime_error("Unable to query group members.", e))
+	}
+
+	fn node_groups(
+		&self,
+		account: AccountId,
+		starting_after: Option<u32>,
+		limit: u32,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Vec<u32>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.node_groups(&at, account, starting_after, limit.min(MAX_PAGE_SIZE))
+			.map_err(|e| runtime_error("Unable to query node groups.", e))
+	}
+
+	fn registered_groups(
+		&self,
+		starting_after: Option<u32>,
+		limit: u32,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Vec<(u32, GroupInfo<AccountId, BlockNumber, Vec<u8>>)>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.registered_groups(&at, starting_after, limit.min(MAX_PAGE_SIZE))
+			.map_err(|e| runtime_error("Unable to query registered groups.", e))
+	}
+
+	fn subscribe_group_events(
+		&self,
+		_metadata: Self::Metadata,
+		subscriber: Subscriber<GroupEventsNotification<<Block as BlockT>::Hash, AccountId, BlockNumber>>,
+		group_id: u32,
+	) {
+		let client = self.client.clone();
+
+		// Merging new best blocks and finalized blocks
+		let best_blocks = self.client.import_notification_stream()
+			.filter(|notification| future::ready(notification.is_new_best))
+			.map(|notification| (notification.hash, NotificationOrigin::Best));
+		// Finality notifications only carry the new finalized head, every block finalized with it is read
+		let finalized_client = self.client.clone();
+		let mut last_finalized = self.client.info().finalized_number;
+		let finalized_blocks = self.client.finality_notification_stream()
+			.flat_map(move |notification| {
+				let hashes = blocks_since(&*finalized_client, last_finalized, notification.hash);
+				last_finalized = last_finalized.max(*notification.header.number());
+				futures::stream::iter(hashes.into_iter().map(|hash| (hash, NotificationOrigin::Finalized)))
+			});
+
+		let notifications = futures::stream::select(best_blocks, finalized_blocks)
+			.filter_map(move |(block_hash, origin)| {
+				let events = client.runtime_api()
+					.group_events(&BlockId::hash(block_hash), group_id)
+					.unwrap_or_else(|e| {
+						warn!("Unable to query group events at {:?}: {:?}", block_hash, e);
+						Vec::new()
+					});
+
+				// Only leader changes are notified again once finalized
+				let events: Vec<_> = match origin {
+					NotificationOrigin::Best => events,
+					NotificationOrigin::Finalized => events.into_iter().filter(|event| event.is_leader_change()).collect(),
+				};
+
+				future::ready(if events.is_empty() {
+					None
+				} else {
+					Some(GroupEventsNotification { block_hash, origin, events })
+				})
+			});
+
+		self.manager.add(subscriber, |sink| {
+			notifications
+				.map(|notification| Ok::<_, ()>(Ok(notification)))
+				.forward(sink.sink_map_err(|e| warn!("Error sending group events notification: {:?}", e)))
+				.map(|_| ())
+		});
+	}
+
+	fn unsubscribe_group_events(
+		&self,
+		_metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> jsonrpc_core::Result<bool> {
+		Ok(self.manager.cancel(id))
+	}
+}
@@ -2,18 +2,296 @@
 
 use super::*;
 
-use frame_system::RawOrigin;
-use frame_benchmarking::{benchmarks, whitelisted_caller, impl_benchmark_test_suite};
+use frame_system::{offchain::AppCrypto, RawOrigin};
+use frame_support::traits::Get;
+use sp_runtime::RuntimeAppPublic;
+use frame_benchmarking::{account, benchmarks, whitelisted_caller, impl_benchmark_test_suite};
+use sp_std::prelude::*;
 #[allow(unused)]
 use crate::Pallet as Archipel;
 
+const SEED: u32 = 0;
+const MAX_GROUPS: u32 = 100;
+const MAX_MEMBERS: u32 = 100;
+
+// Adding account to the federation
+fn member<T: Config>(name: &'static str, index: u32) -> T::AccountId {
+	let member: T::AccountId = account(name, index, SEED);
+	Members::<T>::insert(&member, true);
+	member
+}
+
+// Adding whitelisted caller to the federation
+fn caller_member<T: Config>() -> T::AccountId {
+	let caller: T::AccountId = whitelisted_caller();
+	Members::<T>::insert(&caller, true);
+	caller
+}
+
+// Generating an archipel key in the keystore
+fn node_key<T: Config>() -> T::Public {
+	let runtime_public = <<T::AuthorityId as AppCrypto<T::Public, T::Signature>>::RuntimeAppPublic
+		as RuntimeAppPublic>::generate_pair(None);
+	<T::AuthorityId as AppCrypto<T::Public, T::Signature>>::GenericPublic::from(runtime_public).into()
+}
+
+// Signing a keys proof of account with its archipel key
+fn keys_proof<T: Config>(account: &T::AccountId, public: &T::Public) -> Result<T::Signature, &'static str> {
+	let proof = KeysProof { public: public.clone(), account: account.clone(), nonce: KeysNonce::<T>::get(account) };
+	<T::AuthorityId as AppCrypto<T::Public, T::Signature>>::sign(&proof.encode(), public.clone())
+		.ok_or("Unable to sign keys proof")
+}
+
+// Setting a new archipel key of account
+fn set_node_key<T: Config>(account: &T::AccountId) -> Result<T::Public, &'static str> {
+	let public = node_key::<T>();
+	let signature = keys_proof::<T>(account, &public)?;
+	Archipel::<T>::set_keys(RawOrigin::Signed(account.clone()).into(), public.clone(), signature)?;
+	Ok(public)
+}
+
+// Sending heartbeats of account in groups `0 .. groups`
+fn join_groups<T: Config>(account: &T::AccountId, groups: u32) -> Result<(), &'static str> {
+	let heartbeats: Vec<(u32, NodeStatus)> = (0 .. groups).map(|group_id| (group_id, NodeStatus::Active)).collect();
+	Archipel::<T>::add_heartbeats(RawOrigin::Signed(account.clone()).into(), heartbeats)?;
+	Ok(())
+}
+
+// Taking leadership of groups `0 .. leaders` with account, joining them first
+fn lead_groups<T: Config>(account: &T::AccountId, leaders: u32) -> Result<(), &'static str> {
+	join_groups::<T>(account, leaders)?;
+	for group_id in 0 .. leaders {
+		Archipel::<T>::set_leader(RawOrigin::Signed(account.clone()).into(), account.clone(), group_id, 0, None)?;
+	}
+	Ok(())
+}
+
 benchmarks! {
-	do_something {
-		let s in 0 .. 100;
-		let caller: T::AccountId = whitelisted_caller();
-	}: _(RawOrigin::Signed(caller), s)
+	set_leader {
+		let old_leader = member::<T>("leader", 0);
+		let caller = caller_member::<T>();
+		frame_system::Pallet::<T>::set_block_number(1u32.into());
+		Archipel::<T>::add_heartbeat(RawOrigin::Signed(old_leader.clone()).into(), 1, NodeStatus::Active)?;
+		Archipel::<T>::add_heartbeat(RawOrigin::Signed(caller.clone()).into(), 1, NodeStatus::Passive)?;
+		Archipel::<T>::set_leader(RawOrigin::Signed(old_leader.clone()).into(), old_leader.clone(), 1, 0, None)?;
+
+		// Old leader is dead so leadership can be taken
+		let now = Archipel::<T>::group_alive_time(1) + 2u32.into();
+		frame_system::Pallet::<T>::set_block_number(now);
+	}: _(RawOrigin::Signed(caller.clone()), old_leader, 1, 1, Some(now))
+	verify {
+		assert_eq!(Leaders::<T>::get(1), caller);
+	}
+
+	give_up_leadership {
+		let caller = caller_member::<T>();
+		Archipel::<T>::add_heartbeat(RawOrigin::Signed(caller.clone()).into(), 1, NodeStatus::Active)?;
+		Archipel::<T>::set_leader(RawOrigin::Signed(caller.clone()).into(), caller.clone(), 1, 0, None)?;
+	}: _(RawOrigin::Signed(caller.clone()), 1)
+	verify {
+		assert!(!Leaders::<T>::contains_key(1));
+	}
+
+	add_heartbeat_first {
+		let caller = caller_member::<T>();
+	}: add_heartbeat(RawOrigin::Signed(caller.clone()), 1, NodeStatus::Active)
+	verify {
+		assert_eq!(AccountsCount::<T>::get(), Some(1));
+		assert!(GroupMembers::<T>::contains_key(1, &caller));
+	}
+
+	add_heartbeat {
+		let caller = caller_member::<T>();
+		Archipel::<T>::add_heartbeat(RawOrigin::Signed(caller.clone()).into(), 1, NodeStatus::Passive)?;
+		// Leader heartbeat also renews the lease
+		Archipel::<T>::set_leader(RawOrigin::Signed(caller.clone()).into(), caller.clone(), 1, 0, None)?;
+	}: _(RawOrigin::Signed(caller.clone()), 1, NodeStatus::Active)
+	verify {
+		assert_eq!(Archipel::<T>::get_node_status(&caller, 1), NodeStatus::Active);
+	}
+
+	add_heartbeats {
+		let g in 1 .. MAX_GROUPS;
+		let caller = caller_member::<T>();
+		let heartbeats: Vec<(u32, NodeStatus)> = (0 .. g).map(|group_id| (group_id, NodeStatus::Active)).collect();
+	}: _(RawOrigin::Signed(caller.clone()), heartbeats)
+	verify {
+		assert_eq!(Archipel::<T>::node_groups(&caller, None, g).len(), g as usize);
+	}
+
+	heartbeat {
+		// Node archipel key set by a federation member
+		let account = caller_member::<T>();
+		let public = set_node_key::<T>(&account)?;
+
+		let heartbeat = Heartbeat { public: public.clone(), group_id: 1, status: NodeStatus::Active, block_number: 0u32.into() };
+		let signature = <T::AuthorityId as AppCrypto<T::Public, T::Signature>>::sign(&heartbeat.encode(), public)
+			.ok_or("Unable to sign heartbeat")?;
+	}: _(RawOrigin::None, heartbeat, signature)
+	verify {
+		assert!(GroupMembers::<T>::contains_key(1, &account));
+	}
+
+	add_member {
+		let new_member: T::AccountId = account("member", 0, SEED);
+	}: _(RawOrigin::Root, new_member.clone())
+	verify {
+		assert!(Members::<T>::get(&new_member));
+	}
+
+	remove_member {
+		let g in 1 .. MAX_GROUPS;
+		let l in 0 .. MAX_GROUPS;
+		// Worst case: member node leaves every group it joined and releases every leaded group
+		let old_member = member::<T>("member", 0);
+		let other = member::<T>("other", 0);
+		join_groups::<T>(&old_member, g)?;
+		lead_groups::<T>(&old_member, l.min(g))?;
+		set_node_key::<T>(&old_member)?;
+		// Member is not the last account so accounts index is compacted
+		Archipel::<T>::add_heartbeat(RawOrigin::Signed(other.clone()).into(), 0, NodeStatus::Passive)?;
+	}: _(RawOrigin::Root, old_member.clone(), g, l)
+	verify {
+		assert!(!Members::<T>::get(&old_member));
+		assert!(!AccountsIndex::<T>::contains_key(&old_member));
+		assert_eq!(Leaders::<T>::iter().count(), 0);
+		assert!(!Keys::<T>::contains_key(&old_member));
+	}
+
+	deregister {
+		let g in 1 .. MAX_GROUPS;
+		let l in 0 .. MAX_GROUPS;
+		// Worst case: caller leaves every group it joined and releases every leaded group
+		let caller = caller_member::<T>();
+		let other = member::<T>("member", 0);
+		// Leaders belong to the groups they lead
+		join_groups::<T>(&caller, g)?;
+		lead_groups::<T>(&caller, l.min(g))?;
+		// Caller is not the last account so accounts index is compacted
+		Archipel::<T>::add_heartbeat(RawOrigin::Signed(other.clone()).into(), 0, NodeStatus::Passive)?;
+	}: _(RawOrigin::Signed(caller.clone()), g, l)
+	verify {
+		assert_eq!(AccountsCount::<T>::get(), Some(1));
+		assert_eq!(Accounts::<T>::get(0), other);
+		assert_eq!(Leaders::<T>::iter().count(), 0);
+	}
+
+	force_remove {
+		let g in 1 .. MAX_GROUPS;
+		let l in 0 .. MAX_GROUPS;
+		let node = member::<T>("node", 0);
+		let other = member::<T>("member", 0);
+		join_groups::<T>(&node, g)?;
+		lead_groups::<T>(&node, l.min(g))?;
+		Archipel::<T>::add_heartbeat(RawOrigin::Signed(other.clone()).into(), 0, NodeStatus::Passive)?;
+	}: _(RawOrigin::Root, node.clone(), g, l)
+	verify {
+		assert!(!AccountsIndex::<T>::contains_key(&node));
+		assert_eq!(Archipel::<T>::node_groups(&node, None, g).len(), 0);
+	}
+
+	create_group {
+		let n in 1 .. T::MaxGroupNameLength::get();
+		let admin = caller_member::<T>();
+	}: _(RawOrigin::Root, 1, vec![b'a'; n as usize], ServiceType::Polkadot, admin, 10u32.into(), 20u32.into())
+	verify {
+		assert!(GroupsInfo::<T>::contains_key(1));
+	}
+
+	update_group {
+		let n in 1 .. T::MaxGroupNameLength::get();
+		let caller = caller_member::<T>();
+		let new_admin = member::<T>("admin", 0);
+		Archipel::<T>::create_group(
+			RawOrigin::Root.into(),
+			1,
+			b"group".to_vec(),
+			ServiceType::Polkadot,
+			caller.clone(),
+			10u32.into(),
+			20u32.into(),
+		)?;
+	}: _(RawOrigin::Signed(caller), 1, vec![b'a'; n as usize], ServiceType::Kusama, new_admin, 12u32.into(), 24u32.into())
+	verify {
+		assert_eq!(Archipel::<T>::group_alive_time(1), 12u32.into());
+	}
+
+	remove_group {
+		let caller = caller_member::<T>();
+		Archipel::<T>::create_group(
+			RawOrigin::Root.into(),
+			1,
+			b"group".to_vec(),
+			ServiceType::Polkadot,
+			caller.clone(),
+			10u32.into(),
+			20u32.into(),
+		)?;
+	}: _(RawOrigin::Signed(caller), 1)
+	verify {
+		assert!(!GroupsInfo::<T>::contains_key(1));
+	}
+
+	force_set_leader {
+		let old_leader = member::<T>("leader", 0);
+		let new_leader = member::<T>("member", 0);
+		Archipel::<T>::add_heartbeat(RawOrigin::Signed(old_leader.clone()).into(), 1, NodeStatus::Active)?;
+		Archipel::<T>::add_heartbeat(RawOrigin::Signed(new_leader.clone()).into(), 1, NodeStatus::Passive)?;
+		Archipel::<T>::set_leader(RawOrigin::Signed(old_leader.clone()).into(), old_leader, 1, 0, None)?;
+	}: _(RawOrigin::Root, 1, new_leader.clone())
+	verify {
+		assert_eq!(Leaders::<T>::get(1), new_leader);
+		assert_eq!(LeadershipEpochs::<T>::get(1), 2);
+	}
+
+	force_give_up {
+		let leader = member::<T>("leader", 0);
+		Archipel::<T>::add_heartbeat(RawOrigin::Signed(leader.clone()).into(), 1, NodeStatus::Active)?;
+		Archipel::<T>::set_leader(RawOrigin::Signed(leader.clone()).into(), leader, 1, 0, None)?;
+	}: _(RawOrigin::Root, 1)
+	verify {
+		assert!(!LeadedGroup::<T>::get(1));
+	}
+
+	force_set_status {
+		let node = member::<T>("node", 0);
+		Archipel::<T>::add_heartbeat(RawOrigin::Signed(node.clone()).into(), 1, NodeStatus::Active)?;
+	}: _(RawOrigin::Root, node.clone(), 1, NodeStatus::Maintenance)
+	verify {
+		assert_eq!(Archipel::<T>::get_node_status(&node, 1), NodeStatus::Maintenance);
+	}
+
+	force_clear_group {
+		let m in 1 .. MAX_MEMBERS;
+		for i in 0 .. m {
+			let node = member::<T>("node", i);
+			Archipel::<T>::add_heartbeat(RawOrigin::Signed(node).into(), 1, NodeStatus::Passive)?;
+		}
+		let leader = member::<T>("node", 0);
+		Archipel::<T>::set_leader(RawOrigin::Signed(leader.clone()).into(), leader, 1, 0, None)?;
+	}: _(RawOrigin::Root, 1, m)
+	verify {
+		assert_eq!(GroupMembersCount::<T>::get(1), 0);
+		assert!(!LeadedGroup::<T>::get(1));
+	}
+
+	set_keys {
+		let caller = caller_member::<T>();
+		// Previous key is replaced
+		set_node_key::<T>(&caller)?;
+		let public = node_key::<T>();
+		let signature = keys_proof::<T>(&caller, &public)?;
+	}: _(RawOrigin::Signed(caller.clone()), public.clone(), signature)
+	verify {
+		assert_eq!(KeyOwner::<T>::get(&public), Some(caller));
+	}
+
+	purge_keys {
+		let caller = caller_member::<T>();
+		set_node_key::<T>(&caller)?;
+	}: _(RawOrigin::Signed(caller.clone()))
 	verify {
-		assert_eq!(Something::<T>::get(), Some(s));
+		assert!(!Keys::<T>::contains_key(&caller));
 	}
 }
 
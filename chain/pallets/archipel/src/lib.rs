@@ -6,6 +6,13 @@
 
 pub use pallet::*;
 
+use codec::{Encode, Decode};
+use frame_support::RuntimeDebug;
+#[cfg(feature = "std")]
+use serde::{Serialize, Deserialize};
+use frame_system::offchain::{SignedPayload, SigningTypes};
+use sp_core::crypto::KeyTypeId;
+use sp_std::prelude::*;
 
 #[cfg(test)]
 mod mock;
@@ -16,46 +23,445 @@ mod tests;
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 
+pub mod weights;
+pub mod migrations;
+
+/// Key type of archipel node keys, used by offchain workers to sign heartbeats.
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"arch");
+
+/// Offchain local storage key of the group offchain heartbeats are sent for.
+pub const OFFCHAIN_GROUP_KEY: &[u8] = b"archipel::group";
+
+/// Offchain local storage key of the node status sent with offchain heartbeats.
+pub const OFFCHAIN_STATUS_KEY: &[u8] = b"archipel::status";
+
+/// Archipel node keys.
+pub mod crypto {
+	use super::KEY_TYPE;
+	use sp_core::sr25519::Signature as Sr25519Signature;
+	use sp_runtime::{
+		app_crypto::{app_crypto, sr25519},
+		traits::Verify,
+		MultiSignature, MultiSigner,
+	};
+	app_crypto!(sr25519, KEY_TYPE);
+
+	/// Archipel node key signing transactions of offchain workers.
+	pub struct ArchipelAuthId;
+
+	impl frame_system::offchain::AppCrypto<MultiSigner, MultiSignature> for ArchipelAuthId {
+		type RuntimeAppPublic = Public;
+		type GenericSignature = sp_core::sr25519::Signature;
+		type GenericPublic = sp_core::sr25519::Public;
+	}
+
+	impl frame_system::offchain::AppCrypto<<Sr25519Signature as Verify>::Signer, Sr25519Signature>
+		for ArchipelAuthId
+	{
+		type RuntimeAppPublic = Public;
+		type GenericSignature = sp_core::sr25519::Signature;
+		type GenericPublic = sp_core::sr25519::Public;
+	}
+}
+
+impl<T: Config> sp_runtime::BoundToRuntimeAppPublic for Pallet<T> {
+	type Public = crypto::Public;
+}
+
+// Archipel keys of validators follow their session keys, other nodes map their keys to
+// members through `set_keys` so that they can sign heartbeats too.
+impl<T: Config> frame_support::traits::OneSessionHandler<T::AccountId> for Pallet<T>
+where
+	T::Public: From<sp_core::sr25519::Public>,
+{
+	type Key = crypto::Public;
+
+	fn on_genesis_session<'a, I: 'a>(validators: I)
+	where
+		I: Iterator<Item = (&'a T::AccountId, Self::Key)>,
+	{
+		Self::rotate_session_keys(validators.map(|(account, key)| {
+			(account.clone(), sp_core::sr25519::Public::from(key).into())
+		}));
+	}
+
+	// Keys are checked at every session as genesis members may be set after the genesis session
+	fn on_new_session<'a, I: 'a>(_changed: bool, validators: I, _queued_validators: I)
+	where
+		I: Iterator<Item = (&'a T::AccountId, Self::Key)>,
+	{
+		Self::rotate_session_keys(validators.map(|(account, key)| {
+			(account.clone(), sp_core::sr25519::Public::from(key).into())
+		}));
+	}
+
+	fn on_disabled(_validator_index: usize) {}
+}
+
+/// Storage layout versions of the pallet.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Releases {
+	/// First deployed layout with `Twox64Concat` account keys and a single group per node.
+	V1_0_0,
+	/// Account keys hashed with `Blake2_128Concat`, federation members and group members.
+	V2_0_0,
+}
+
+impl Default for Releases {
+	fn default() -> Self {
+		Releases::V1_0_0
+	}
+}
+
+/// Service mode reported by a node in its heartbeats.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum NodeStatus {
+	/// Service mode is not known yet.
+	Unknown,
+	/// Service is running in active mode.
+	Active,
+	/// Service is running in passive mode.
+	Passive,
+	/// Node is in maintenance and doesn't run the service.
+	Maintenance,
+}
+
+impl Default for NodeStatus {
+	fn default() -> Self {
+		NodeStatus::Unknown
+	}
+}
+
+/// Liveness of a node inside a group.
+#[derive(Encode, Decode, Clone, Copy, Default, PartialEq, Eq, RuntimeDebug)]
+pub struct MemberState<BlockNumber> {
+	/// Block of the last heartbeat sent by the node for this group.
+	pub last_heartbeat: BlockNumber,
+	/// Status reported with the last heartbeat.
+	pub status: NodeStatus,
+}
+
+/// State of a group member as seen at one block.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct GroupMemberState<AccountId, BlockNumber> {
+	/// Member account.
+	pub account: AccountId,
+	/// Block of the last heartbeat sent by the member for this group.
+	pub last_heartbeat: BlockNumber,
+	/// Status reported with the last heartbeat.
+	pub status: NodeStatus,
+	/// Member sent a heartbeat within the group alive time.
+	pub alive: bool,
+}
+
+/// Leadership and members of a group as seen at one block.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct GroupState<AccountId, BlockNumber> {
+	/// Block the state was read at.
+	pub block_number: BlockNumber,
+	/// Current leader if the group is leaded.
+	pub leader: Option<AccountId>,
+	/// Current leadership epoch.
+	pub epoch: u64,
+	/// Block at which the current leadership ends unless renewed.
+	pub lease_end: Option<BlockNumber>,
+	/// Members of the group with their liveness.
+	pub members: Vec<GroupMemberState<AccountId, BlockNumber>>,
+}
+
+/// Leadership of a group read at one block.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct Leadership<AccountId, BlockNumber> {
+	/// Block the leadership was read at.
+	pub block_number: BlockNumber,
+	/// Current leader if the group is leaded.
+	pub leader: Option<AccountId>,
+	/// Current leadership epoch.
+	pub epoch: u64,
+	/// Block at which the current leadership ends unless renewed.
+	pub lease_end: Option<BlockNumber>,
+}
+
+/// Archipel event concerning a single group.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum GroupEvent<AccountId, BlockNumber> {
+	/// Node sent a heartbeat for the group.
+	#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+	NewHeartbeat {
+		/// Node account.
+		account: AccountId,
+		/// Status reported by the node.
+		status: NodeStatus,
+		/// Block of the heartbeat.
+		block_number: BlockNumber,
+	},
+	/// Node took group leadership.
+	NewLeader {
+		/// New leader account.
+		account: AccountId,
+		/// New leadership epoch.
+		epoch: u64,
+	},
+	/// Leader gave up group leadership.
+	GiveUpLeader {
+		/// Previous leader account.
+		account: AccountId,
+		/// Leadership epoch that ended.
+		epoch: u64,
+	},
+}
+
+impl<AccountId, BlockNumber> GroupEvent<AccountId, BlockNumber> {
+	/// Event changes the group leader.
+	pub fn is_leader_change(&self) -> bool {
+		!matches!(self, GroupEvent::NewHeartbeat { .. })
+	}
+}
+
+/// Heartbeat of a node signed with its archipel key and submitted without fees.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Heartbeat<Public, BlockNumber> {
+	/// Archipel key of the node, identifying its account.
+	pub public: Public,
+	/// Group the heartbeat is sent for.
+	pub group_id: u32,
+	/// Status reported by the node.
+	pub status: NodeStatus,
+	/// Block the heartbeat was created at.
+	pub block_number: BlockNumber,
+}
+
+impl<T: SigningTypes> SignedPayload<T> for Heartbeat<T::Public, T::BlockNumber> {
+	fn public(&self) -> T::Public {
+		self.public.clone()
+	}
+}
+
+/// Proof that a member owns the archipel key it sets, signed with that key.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct KeysProof<Public, AccountId> {
+	/// Archipel key being set.
+	pub public: Public,
+	/// Member the key will sign heartbeats for.
+	pub account: AccountId,
+	/// Keys nonce of the member, a proof can be used only once.
+	pub nonce: u32,
+}
+
+impl<T: SigningTypes> SignedPayload<T> for KeysProof<T::Public, T::AccountId> {
+	fn public(&self) -> T::Public {
+		self.public.clone()
+	}
+}
+
+/// Action an orchestrator should take for its service in a group.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum RecommendedAction {
+	/// Take group leadership and start service in active mode.
+	BecomeLeader,
+	/// Keep group leadership and service in active mode.
+	StayActive,
+	/// Keep service in passive mode.
+	StayPassive,
+	/// Give up group leadership and switch service to passive mode.
+	GiveUp,
+}
+
+/// Reason of a recommended action.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum ActionReason {
+	/// Node is not a federation member.
+	NotMember,
+	/// Node didn't send a heartbeat for the group within the group alive time.
+	NodeNotAlive,
+	/// Node reported maintenance status for the group.
+	Maintenance,
+	/// No other group member sent a heartbeat within the group alive time.
+	NoOtherMemberAlive,
+	/// Group has no leader.
+	NoLeader,
+	/// Node is the group leader.
+	IsLeader,
+	/// Another node leads the group and is alive.
+	LeaderAlive,
+	/// Another node leads the group but is not alive anymore.
+	LeaderNotAlive,
+}
+
+/// Recommended action with its reason and the leadership epoch it was computed at.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct Recommendation<AccountId> {
+	/// Action to take.
+	pub action: RecommendedAction,
+	/// Reason of the action.
+	pub reason: ActionReason,
+	/// Current group leader if the group is leaded.
+	pub leader: Option<AccountId>,
+	/// Current leadership epoch to report when taking leadership.
+	pub epoch: u64,
+}
+
+/// Kind of service managed by a group.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum ServiceType {
+	/// Polkadot validator.
+	Polkadot,
+	/// Kusama validator.
+	Kusama,
+	/// Centrifuge validator.
+	Centrifuge,
+	/// Trustlines validator.
+	Trustlines,
+}
+
+/// Metadata and leadership parameters of a registered group.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct GroupInfo<AccountId, BlockNumber, Name> {
+	/// Human readable name of the group.
+	pub name: Name,
+	/// Service managed by the group.
+	pub service: ServiceType,
+	/// Account allowed to update or remove the group along with the admin origin.
+	pub admin: AccountId,
+	/// Number of blocks without heartbeat after which the group leader can be replaced.
+	pub alive_time: BlockNumber,
+	/// Number of blocks the group leadership lasts unless renewed by leader heartbeats.
+	pub lease_duration: BlockNumber,
+}
 
 #[frame_support::pallet]
 pub mod pallet {
 	use frame_support::{
 		dispatch::DispatchResult,
-		pallet_prelude::*
+		pallet_prelude::*,
+		sp_runtime::traits::Zero,
+		storage::{bounded_vec::BoundedVec, unhashed, StoragePrefixedMap},
+		Blake2_128Concat, StorageHasher, Twox64Concat,
+	};
+	use frame_system::{
+		offchain::{AppCrypto, SendTransactionTypes, SendUnsignedTransaction, SignedPayload, Signer, SigningTypes},
+		pallet_prelude::*,
+	};
+	use sp_runtime::{offchain::storage::StorageValueRef, traits::IdentifyAccount};
+	use sp_std::{convert::{TryFrom, TryInto}, prelude::*};
+	use super::{
+		ActionReason, GroupEvent, GroupInfo, GroupMemberState, GroupState, Heartbeat, KeysProof, Leadership, MemberState,
+		NodeStatus, Recommendation, RecommendedAction, Releases, ServiceType, OFFCHAIN_GROUP_KEY, OFFCHAIN_STATUS_KEY,
 	};
-	use frame_system::pallet_prelude::*;
+	use crate::weights::WeightInfo;
+
+	/// Group info as stored by the pallet.
+	pub type GroupInfoOf<T> = GroupInfo<
+		<T as frame_system::Config>::AccountId,
+		<T as frame_system::Config>::BlockNumber,
+		BoundedVec<u8, <T as Config>::MaxGroupNameLength>,
+	>;
 
 	/// Configure the pallet by specifying the parameters and types on which it depends.
 	#[pallet::config]
-	pub trait Config: frame_system::Config {
+	pub trait Config: SendTransactionTypes<Call<Self>> + SigningTypes + frame_system::Config {
 		/// Because this pallet emits events, it depends on the runtime's definition of an event.
 		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// Key signing heartbeats sent by offchain workers.
+		type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
+
+		/// Origin allowed to add and remove federation members and to force leadership recovery.
+		type AdminOrigin: EnsureOrigin<Self::Origin>;
+
+		/// Number of blocks without heartbeat after which a leader can be replaced.
+		/// Used by groups without registered parameters.
+		#[pallet::constant]
+		type AliveTime: Get<Self::BlockNumber>;
+
+		/// Number of blocks a leadership lasts unless renewed by leader heartbeats.
+		/// Used by groups without registered parameters.
+		#[pallet::constant]
+		type LeaseDuration: Get<Self::BlockNumber>;
+
+		/// Maximum length of a group name.
+		#[pallet::constant]
+		type MaxGroupNameLength: Get<u32>;
+
+		/// Number of blocks without heartbeat after which a node is pruned.
+		#[pallet::constant]
+		type PruneAfter: Get<Self::BlockNumber>;
+
+		/// Number of blocks between two heartbeats sent by offchain workers, zero disables them.
+		#[pallet::constant]
+		type HeartbeatInterval: Get<Self::BlockNumber>;
+
+		/// Federation members added by the migration to `Releases::V2_0_0` to nodes serving a group.
+		type MigrationMembers: Get<Vec<Self::AccountId>>;
+
+		/// Priority of unsigned heartbeats in the transaction pool.
+		#[pallet::constant]
+		type UnsignedPriority: Get<TransactionPriority>;
+
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
 	}
 
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
 	pub struct Pallet<T>(_);
 
-	// Heartbeats storage
+	// Last heartbeat of a node in any group storage
 	#[pallet::storage]
 	#[pallet::getter(fn get_heartbeat)]
-	pub(super) type Heartbeats<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, T::BlockNumber, ValueQuery>;
-
-	// Status storage
-	#[pallet::storage]
-	#[pallet::getter(fn get_node_status)]
-	pub(super) type NodesStatus<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, u32, ValueQuery>;
+	pub(super) type Heartbeats<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, T::BlockNumber, ValueQuery>;
 
-	// Groups storage
+	// Groups of a node storage
 	#[pallet::storage]
-	#[pallet::getter(fn get_group)]
-	pub(super) type Groups<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, u32, ValueQuery>;
+	#[pallet::getter(fn is_in_group)]
+	pub(super) type Groups<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat, T::AccountId,
+		Twox64Concat, u32,
+		bool,
+		ValueQuery
+	>;
 
 	// Current leaders storage
 	#[pallet::storage]
 	#[pallet::getter(fn get_leader)]
 	pub(super) type Leaders<T: Config> = StorageMap<_, Twox64Concat, u32, T::AccountId, ValueQuery>;
 
+	// Leadership epochs storage
+	#[pallet::storage]
+	#[pallet::getter(fn get_leadership_epoch)]
+	pub(super) type LeadershipEpochs<T: Config> = StorageMap<_, Twox64Concat, u32, u64, ValueQuery>;
+
+	// Leadership lease end storage
+	#[pallet::storage]
+	#[pallet::getter(fn get_leader_lease)]
+	pub(super) type LeaderLease<T: Config> = StorageMap<_, Twox64Concat, u32, T::BlockNumber, ValueQuery>;
+
+	// Groups whose leader lease ends at a block storage, entries of renewed or released leases are skipped
+	#[pallet::storage]
+	#[pallet::getter(fn get_lease_expiries)]
+	pub(super) type LeaseExpiries<T: Config> = StorageMap<_, Twox64Concat, T::BlockNumber, Vec<u32>, ValueQuery>;
+
+	// Groups whose leader is not alive anymore from a block unless it sent a heartbeat since storage
+	#[pallet::storage]
+	#[pallet::getter(fn get_stale_checks)]
+	pub(super) type StaleChecks<T: Config> = StorageMap<_, Twox64Concat, T::BlockNumber, Vec<u32>, ValueQuery>;
+
 	// Current leaders storage
 	#[pallet::storage]
 	#[pallet::getter(fn get_leaded_group)]
@@ -72,7 +478,109 @@ pub mod pallet {
 
 	#[pallet::storage]
 	#[pallet::getter(fn get_accounts_index)]
-	pub(super) type AccountsIndex<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, u32, ValueQuery>;
+	pub(super) type AccountsIndex<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+	// Index of the next account checked by pruning
+	#[pallet::storage]
+	#[pallet::getter(fn get_prune_cursor)]
+	pub(super) type PruneCursor<T> = StorageValue<_, u32, ValueQuery>;
+
+	// Group members storage
+	#[pallet::storage]
+	#[pallet::getter(fn get_group_member)]
+	pub(super) type GroupMembers<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat, u32,
+		Blake2_128Concat, T::AccountId,
+		MemberState<T::BlockNumber>,
+		OptionQuery
+	>;
+
+	// Group members count storage
+	#[pallet::storage]
+	#[pallet::getter(fn get_group_members_count)]
+	pub(super) type GroupMembersCount<T: Config> = StorageMap<_, Twox64Concat, u32, u32, ValueQuery>;
+
+	// Groups registry storage
+	#[pallet::storage]
+	#[pallet::getter(fn get_group_info)]
+	pub(super) type GroupsInfo<T: Config> = StorageMap<_, Twox64Concat, u32, GroupInfoOf<T>, OptionQuery>;
+
+	// Federation members storage
+	#[pallet::storage]
+	#[pallet::getter(fn is_member)]
+	pub(super) type Members<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, bool, ValueQuery>;
+
+	// Archipel key of a member storage
+	#[pallet::storage]
+	#[pallet::getter(fn get_keys)]
+	pub(super) type Keys<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, T::Public, OptionQuery>;
+
+	// Member owning an archipel key storage
+	#[pallet::storage]
+	#[pallet::getter(fn get_key_owner)]
+	pub(super) type KeyOwner<T: Config> = StorageMap<_, Blake2_128Concat, T::Public, T::AccountId, OptionQuery>;
+
+	// Number of archipel keys set by a member storage
+	#[pallet::storage]
+	#[pallet::getter(fn keys_nonce)]
+	pub(super) type KeysNonce<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+	// Storage layout version
+	#[pallet::storage]
+	#[pallet::getter(fn storage_version)]
+	pub(super) type StorageVersion<T> = StorageValue<_, Releases, ValueQuery>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// Accounts allowed to take part in the federation from genesis.
+		pub members: Vec<T::AccountId>,
+		/// Initial groups of federation members.
+		pub groups: Vec<(T::AccountId, u32)>,
+		/// Initial leader of each group, leaders must belong to the group they lead.
+		pub leaders: Vec<(u32, T::AccountId)>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self {
+				members: Default::default(),
+				groups: Default::default(),
+				leaders: Default::default(),
+			}
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			<StorageVersion<T>>::put(Releases::V2_0_0);
+
+			for member in &self.members {
+				<Members<T>>::insert(member, true);
+			}
+
+			// Group members start without heartbeat
+			for (account, group_id) in &self.groups {
+				assert!(<Members<T>>::get(account), "Group members must be federation members");
+				Pallet::<T>::add_account(account).expect("Adding account can't fail; qed");
+				Pallet::<T>::insert_group_member(account, *group_id, Default::default());
+			}
+
+			for (group_id, leader) in &self.leaders {
+				assert!(<Groups<T>>::get(leader, group_id), "Group leaders must belong to their group");
+				assert!(!<Leaders<T>>::contains_key(group_id), "Group can't have several leaders");
+
+				// Leader must start sending heartbeats before its lease ends
+				<Leaders<T>>::insert(group_id, leader);
+				<LeadedGroup<T>>::insert(group_id, true);
+				Pallet::<T>::start_lease(*group_id, Zero::zero());
+				Pallet::<T>::schedule_stale_check(*group_id, Zero::zero(), Zero::zero());
+				<LeadershipEpochs<T>>::insert(group_id, 1);
+			}
+		}
+	}
 
 	// Pallets use events to inform users when important changes are made.
 	// https://substrate.dev/docs/en/knowledgebase/runtime/events
@@ -82,18 +590,269 @@ pub mod pallet {
 	pub enum Event<T: Config> {
 		/// Event documentation should end with an array that provides descriptive names for event
 		// Heartbeat event
-		NewHeartbeat(T::AccountId, u32, u32, T::BlockNumber),
+		NewHeartbeat(T::AccountId, u32, NodeStatus, T::BlockNumber),
 		// New leader event
-		NewLeader(T::AccountId, u32),
+		NewLeader(T::AccountId, u32, u64),
 		// Give up Leader event
-		GiveUpLeader(T::AccountId, u32),
+		GiveUpLeader(T::AccountId, u32, u64),
+		// Member added to federation event
+		MemberAdded(T::AccountId),
+		// Member removed from federation event
+		MemberRemoved(T::AccountId),
+		// Leader lease expired event
+		LeaderExpired(T::AccountId, u32),
+		// Leader stopped sending heartbeats event
+		LeaderStale(T::AccountId, u32, T::BlockNumber),
+		// Group registered event
+		GroupCreated(u32, T::AccountId),
+		// Group metadata or parameters updated event
+		GroupUpdated(u32),
+		// Group unregistered event
+		GroupRemoved(u32),
+		// Node and its heartbeats removed event
+		NodeRemoved(T::AccountId),
+		// Member archipel key set event
+		KeysSet(T::AccountId),
+		// Member archipel key removed event
+		KeysPurged(T::AccountId),
+		// Leader set by admin event
+		LeaderForced(T::AccountId, u32, u64),
+		// Leadership released by admin event
+		GiveUpForced(T::AccountId, u32, u64),
+		// Node status set by admin event
+		StatusForced(T::AccountId, u32, NodeStatus),
+		// Group members and leadership cleared by admin event
+		GroupCleared(u32, u32),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// Sender is not a member of the federation.
+		NotMember,
+		/// Account is already a member of the federation.
+		AlreadyMember,
+		/// Current leader sent a heartbeat for this group less than `AliveTime` blocks ago.
+		LeaderStillAlive,
+		/// Sender is already the leader of this group.
+		AlreadyLeader,
+		/// Reported old leader is not the current leader of this group.
+		IncorrectOldLeader,
+		/// There is no leader in this group.
+		NoLeader,
+		/// Sender is not the current leader of this group.
+		NotLeader,
+		/// Leadership of this group changed since the reported epoch.
+		StaleEpoch,
+		/// Leadership request was submitted after its validity block.
+		RequestExpired,
+		/// Group is already registered.
+		GroupAlreadyExists,
+		/// Group is not registered.
+		UnknownGroup,
+		/// Sender is not the admin of this group.
+		NotGroupAdmin,
+		/// Group name is longer than `MaxGroupNameLength`.
+		GroupNameTooLong,
+		/// Group alive time and lease duration must be greater than zero.
+		InvalidGroupParameters,
+		/// Account never sent a heartbeat or was already removed.
+		UnknownNode,
+		/// Archipel key is already set by another member.
+		KeyInUse,
+		/// Keys proof is not signed by the archipel key for the sender and its keys nonce.
+		InvalidKeysProof,
+		/// Member has no archipel key set.
+		NoKeys,
+		/// Account is not a member of this group.
+		NotGroupMember,
+		/// Group members, node groups or leaders are more than the provided witness.
+		InvalidWitness,
 	}
 
 
 	#[pallet::hooks]
-	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			let expiries = <LeaseExpiries<T>>::take(now);
+			let stale_checks = <StaleChecks<T>>::take(now);
+			let mut writes: Weight = 2;
+
+			for group_id in expiries.iter() {
+				// Lease was renewed or released since it was indexed
+				if <LeaderLease<T>>::try_get(group_id) != Ok(now) {
+					continue;
+				}
+
+				// Leader didn't renew its lease so the group is not leaded anymore
+				let leader = <Leaders<T>>::take(group_id);
+				<LeadedGroup<T>>::insert(group_id, false);
+				<LeaderLease<T>>::remove(group_id);
+				writes = writes.saturating_add(3);
+
+				Self::deposit_event(Event::LeaderExpired(leader, *group_id));
+			}
+
+			for group_id in stale_checks.iter() {
+				if !<LeaderLease<T>>::contains_key(group_id) {
+					continue;
+				}
+
+				// Leader is not alive anymore since this block but still holds its lease
+				let leader = Self::get_leader(group_id);
+				let last_heartbeat = Self::get_group_heartbeat(&leader, *group_id);
+				if now == Self::stale_block(*group_id, last_heartbeat) {
+					Self::deposit_event(Event::LeaderStale(leader, *group_id, last_heartbeat));
+				}
+			}
+
+			let reads = (expiries.len() as Weight).saturating_mul(2)
+				.saturating_add((stale_checks.len() as Weight).saturating_mul(4))
+				.saturating_add(2);
+			T::DbWeight::get().reads_writes(reads, writes)
+		}
+
+		fn on_runtime_upgrade() -> Weight {
+			if Self::storage_version() == Releases::V1_0_0 {
+				crate::migrations::v2::migrate::<T>()
+			} else {
+				T::DbWeight::get().reads(1)
+			}
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<(), &'static str> {
+			crate::migrations::v2::pre_migrate::<T>()
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade() -> Result<(), &'static str> {
+			crate::migrations::v2::post_migrate::<T>()
+		}
+
+		fn on_idle(now: T::BlockNumber, remaining_weight: Weight) -> Weight {
+			let prune_after = T::PruneAfter::get();
+			let db_weight = T::DbWeight::get();
+			// Reading account and its last heartbeat
+			let check_weight = 10_000 + db_weight.reads(2);
+
+			let mut used_weight = db_weight.reads_writes(2, 1);
+			if used_weight.saturating_add(check_weight) > remaining_weight {
+				return 0;
+			}
+			let mut cursor = Self::get_prune_cursor();
+			let mut checked: u32 = 0;
+			// Leaders are counted once, pruning can only release leaderships
+			let mut leaders_count: Option<u32> = None;
+
+			loop {
+				let accounts_count = Self::get_accounts_count().unwrap_or(0);
+				if accounts_count == 0 || checked >= accounts_count {
+					break;
+				}
+				if used_weight.saturating_add(check_weight) > remaining_weight {
+					break;
+				}
+
+				// Restarting from the first account once the end of the index is reached
+				if cursor >= accounts_count {
+					cursor = 0;
+				}
+
+				let account = Self::get_account(cursor);
+				used_weight = used_weight.saturating_add(check_weight);
+				checked = checked.saturating_add(1);
+
+				// Nodes that never sent a heartbeat, like genesis group members, are not pruned
+				let stale = <Heartbeats<T>>::try_get(&account)
+					.map_or(false, |last_heartbeat| now.saturating_sub(last_heartbeat) > prune_after);
+				if !stale {
+					cursor = cursor.saturating_add(1);
+					continue;
+				}
+
+				// Counting groups and leaders to know the removal cost before removing the node
+				let groups = <Groups<T>>::iter_prefix(&account).count() as u32;
+				used_weight = used_weight.saturating_add(db_weight.reads(groups as Weight));
+				let leaders = *leaders_count.get_or_insert_with(|| {
+					let count = <Leaders<T>>::iter().count() as u32;
+					used_weight = used_weight.saturating_add(db_weight.reads(count as Weight));
+					count
+				});
+
+				// Node is checked again at next idle block if it can't be removed in this one
+				if used_weight.saturating_add(Self::remove_node_weight(groups, leaders)) > remaining_weight {
+					break;
+				}
+
+				// Last account is moved at cursor position so cursor stays in place
+				used_weight = used_weight.saturating_add(Self::remove_node(&account));
+			}
+
+			<PruneCursor<T>>::put(cursor);
+
+			used_weight
+		}
+
+		fn offchain_worker(now: T::BlockNumber) {
+			let interval = T::HeartbeatInterval::get();
+			if interval.is_zero() || !(now % interval).is_zero() {
+				return;
+			}
+
+			if let Err(e) = Self::send_heartbeat(now) {
+				log::debug!(target: "archipel", "Offchain heartbeat not sent at {:?}: {}", now, e);
+			}
+		}
+	}
 
 	impl<T: Config> Pallet<T> {
+		// Reading a value set in offchain local storage by the node operator
+		fn offchain_value<V: Decode>(key: &[u8]) -> Option<V> {
+			StorageValueRef::persistent(key).get::<V>().ok().flatten()
+		}
+
+		// Sending an unsigned heartbeat signed with the local archipel key
+		fn send_heartbeat(now: T::BlockNumber) -> Result<(), &'static str> {
+			let group_id = Self::offchain_value::<u32>(OFFCHAIN_GROUP_KEY)
+				.ok_or("No group set in offchain local storage")?;
+			let status = Self::offchain_value::<NodeStatus>(OFFCHAIN_STATUS_KEY)
+				.ok_or("No status set in offchain local storage")?;
+
+			let signer = Signer::<T, T::AuthorityId>::any_account();
+			let result = signer.send_unsigned_transaction(
+				|account| Heartbeat { public: account.public.clone(), group_id, status, block_number: now },
+				|heartbeat, signature| Call::heartbeat(heartbeat, signature),
+			);
+			match result {
+				Some((_, Ok(()))) => Ok(()),
+				Some((_, Err(()))) => Err("Heartbeat transaction submission failed"),
+				None => Err("No local archipel key to sign heartbeat"),
+			}
+		}
+
+		// Recording a heartbeat of a member for a group
+		fn record_heartbeat(account: &T::AccountId, group_id: u32, node_status: NodeStatus) -> DispatchResultWithPostInfo {
+			let now = frame_system::Pallet::<T>::block_number();
+
+			// Repeated heartbeats don't add node to accounts and group members
+			let first_heartbeat = !<AccountsIndex<T>>::contains_key(account)
+				|| !<GroupMembers<T>>::contains_key(group_id, account);
+
+			// Adding account in map
+			Self::add_account(account)?;
+
+			// Adding Now into Heartbeats map
+			<Heartbeats<T>>::insert(account, now);
+
+			Self::do_heartbeat(account, group_id, node_status, now);
+
+			if first_heartbeat {
+				Ok(().into())
+			} else {
+				Ok(Some(T::WeightInfo::add_heartbeat()).into())
+			}
+		}
+
 		// Adding account
 		fn add_account(account: &T::AccountId) -> DispatchResult {
 			// Checking if account exists already in storage
@@ -111,6 +870,474 @@ pub mod pallet {
 			}
 			Ok(())
 		}
+
+		// Recording a heartbeat of account for a group
+		fn do_heartbeat(account: &T::AccountId, group_id: u32, node_status: NodeStatus, now: T::BlockNumber) {
+			Self::insert_group_member(account, group_id, MemberState { last_heartbeat: now, status: node_status });
+
+			// Renewing lease if account is the group leader
+			if <LeaderLease<T>>::contains_key(group_id) && Self::get_leader(group_id) == *account {
+				Self::start_lease(group_id, now);
+				Self::schedule_stale_check(group_id, now, now);
+			}
+
+			// Triggering heartbeats update event
+			Self::deposit_event(Event::NewHeartbeat(account.clone(), group_id, node_status, now));
+		}
+
+		// Adding account into group members map
+		pub(crate) fn insert_group_member(account: &T::AccountId, group_id: u32, state: MemberState<T::BlockNumber>) {
+			if !<GroupMembers<T>>::contains_key(group_id, account) {
+				<GroupMembersCount<T>>::mutate(group_id, |count| *count = count.saturating_add(1));
+				<Groups<T>>::insert(account, group_id, true);
+			}
+			<GroupMembers<T>>::insert(group_id, account, state);
+		}
+
+		/// Last heartbeat sent by account for a group.
+		pub fn get_group_heartbeat(account: &T::AccountId, group_id: u32) -> T::BlockNumber {
+			Self::get_group_member(group_id, account)
+				.map(|state| state.last_heartbeat)
+				.unwrap_or_else(Zero::zero)
+		}
+
+		/// Status reported by account for a group.
+		pub fn get_node_status(account: &T::AccountId, group_id: u32) -> NodeStatus {
+			Self::get_group_member(group_id, account)
+				.map(|state| state.status)
+				.unwrap_or_default()
+		}
+
+		/// Group event matching an archipel event of a group.
+		pub fn group_event(event: &Event<T>, group_id: u32) -> Option<GroupEvent<T::AccountId, T::BlockNumber>> {
+			match event {
+				Event::NewHeartbeat(account, group, status, block_number) if *group == group_id => {
+					Some(GroupEvent::NewHeartbeat {
+						account: account.clone(),
+						status: *status,
+						block_number: *block_number,
+					})
+				}
+				Event::NewLeader(account, group, epoch) if *group == group_id => {
+					Some(GroupEvent::NewLeader { account: account.clone(), epoch: *epoch })
+				}
+				Event::GiveUpLeader(account, group, epoch) if *group == group_id => {
+					Some(GroupEvent::GiveUpLeader { account: account.clone(), epoch: *epoch })
+				}
+				// Forced leadership changes are seen by orchestrators as regular ones
+				Event::LeaderForced(account, group, epoch) if *group == group_id => {
+					Some(GroupEvent::NewLeader { account: account.clone(), epoch: *epoch })
+				}
+				Event::GiveUpForced(account, group, epoch) if *group == group_id => {
+					Some(GroupEvent::GiveUpLeader { account: account.clone(), epoch: *epoch })
+				}
+				_ => None,
+			}
+		}
+
+		/// Leadership of a group at current block.
+		pub fn leadership(group_id: u32) -> Leadership<T::AccountId, T::BlockNumber> {
+			let leader = if Self::get_leaded_group(group_id) {
+				<Leaders<T>>::try_get(group_id).ok()
+			} else {
+				None
+			};
+
+			Leadership {
+				block_number: frame_system::Pallet::<T>::block_number(),
+				leader,
+				epoch: Self::get_leadership_epoch(group_id),
+				lease_end: <LeaderLease<T>>::try_get(group_id).ok(),
+			}
+		}
+
+		/// Storage keys holding the leadership of a group, used to prove it to light clients.
+		pub fn leadership_keys(group_id: u32) -> Vec<Vec<u8>> {
+			sp_std::vec![
+				<Leaders<T>>::hashed_key_for(group_id),
+				<LeadershipEpochs<T>>::hashed_key_for(group_id),
+				<LeadedGroup<T>>::hashed_key_for(group_id),
+			]
+		}
+
+		/// Leadership and members liveness of a group at current block.
+		pub fn group_state(group_id: u32) -> GroupState<T::AccountId, T::BlockNumber> {
+			let now = frame_system::Pallet::<T>::block_number();
+			let alive_time = Self::group_alive_time(group_id);
+			let leadership = Self::leadership(group_id);
+
+			let members = <GroupMembers<T>>::iter_prefix(group_id)
+				.map(|(account, state)| Self::member_state(account, state, now, alive_time))
+				.collect();
+
+			GroupState {
+				block_number: now,
+				leader: leadership.leader,
+				epoch: leadership.epoch,
+				lease_end: leadership.lease_end,
+				members,
+			}
+		}
+
+		/// Action account should take for its service in a group at current block.
+		///
+		/// Every orchestrator follows these rules in order:
+		/// a node must be a member, be alive and not in maintenance, and see another
+		/// alive member before leading. It then takes leadership of a group without
+		/// leader or with a dead leader, keeps its own leadership, or stays passive.
+		pub fn recommended_action(account: &T::AccountId, group_id: u32) -> Recommendation<T::AccountId> {
+			let now = frame_system::Pallet::<T>::block_number();
+			let alive_time = Self::group_alive_time(group_id);
+			let epoch = Self::get_leadership_epoch(group_id);
+			let leader = if Self::get_leaded_group(group_id) {
+				<Leaders<T>>::try_get(group_id).ok()
+			} else {
+				None
+			};
+			let is_leader = leader.as_ref() == Some(account);
+
+			// Leaving leadership or staying passive if node can't run the service
+			let stop = |reason: ActionReason| Recommendation {
+				action: if is_leader { RecommendedAction::GiveUp } else { RecommendedAction::StayPassive },
+				reason,
+				leader: leader.clone(),
+				epoch,
+			};
+			let recommend = |action: RecommendedAction, reason: ActionReason| Recommendation { action, reason, leader: leader.clone(), epoch };
+
+			if !Self::is_member(account) {
+				return stop(ActionReason::NotMember);
+			}
+
+			let state = Self::get_group_member(group_id, account).unwrap_or_default();
+			if !Self::is_alive(state.last_heartbeat, now, alive_time) {
+				return stop(ActionReason::NodeNotAlive);
+			}
+			if state.status == NodeStatus::Maintenance {
+				return stop(ActionReason::Maintenance);
+			}
+
+			let other_alive = <GroupMembers<T>>::iter_prefix(group_id)
+				.any(|(member, state)| member != *account && Self::is_alive(state.last_heartbeat, now, alive_time));
+			if !other_alive {
+				return stop(ActionReason::NoOtherMemberAlive);
+			}
+
+			match leader.as_ref() {
+				None => recommend(RecommendedAction::BecomeLeader, ActionReason::NoLeader),
+				Some(_) if is_leader => recommend(RecommendedAction::StayActive, ActionReason::IsLeader),
+				Some(leader_account) => {
+					let leader_heartbeat = Self::get_group_heartbeat(leader_account, group_id);
+					if now.saturating_sub(leader_heartbeat) > alive_time {
+						recommend(RecommendedAction::BecomeLeader, ActionReason::LeaderNotAlive)
+					} else {
+						recommend(RecommendedAction::StayPassive, ActionReason::LeaderAlive)
+					}
+				}
+			}
+		}
+
+		// Checking if a heartbeat was sent within alive time
+		fn is_alive(last_heartbeat: T::BlockNumber, now: T::BlockNumber, alive_time: T::BlockNumber) -> bool {
+			!last_heartbeat.is_zero() && now.saturating_sub(last_heartbeat) <= alive_time
+		}
+
+		// Building group member state as seen at block `now`
+		fn member_state(
+			account: T::AccountId,
+			state: MemberState<T::BlockNumber>,
+			now: T::BlockNumber,
+			alive_time: T::BlockNumber,
+		) -> GroupMemberState<T::AccountId, T::BlockNumber> {
+			GroupMemberState {
+				account,
+				last_heartbeat: state.last_heartbeat,
+				status: state.status,
+				alive: Self::is_alive(state.last_heartbeat, now, alive_time),
+			}
+		}
+
+		// Reading at most `limit` entries of a map prefix in storage order, after the `starting_after` key.
+		// `hash_len` is the length of the hash preceding the encoded key of the entries.
+		fn page_after<K: Decode, V: Decode>(
+			prefix: Vec<u8>,
+			hash_len: usize,
+			starting_after: Option<Vec<u8>>,
+			limit: u32,
+		) -> Vec<(K, V)> {
+			let mut previous_key = starting_after.unwrap_or_else(|| prefix.clone());
+			let mut page = Vec::new();
+
+			while page.len() < limit as usize {
+				let key = match sp_io::storage::next_key(&previous_key) {
+					Some(key) if key.starts_with(&prefix) => key,
+					_ => break,
+				};
+				let entry = key.get(prefix.len().saturating_add(hash_len)..)
+					.and_then(|mut raw_key| K::decode(&mut raw_key).ok())
+					.zip(unhashed::get::<V>(&key));
+				page.extend(entry);
+				previous_key = key;
+			}
+
+			page
+		}
+
+		/// Groups for which account sent heartbeats, at most `limit` groups following `starting_after`.
+		///
+		/// Groups are sorted by storage key, a page keeps its place when groups are added or removed.
+		pub fn node_groups(account: &T::AccountId, starting_after: Option<u32>, limit: u32) -> Vec<u32> {
+			let prefix = [&<Groups<T>>::final_prefix()[..], &Blake2_128Concat::hash(&account.encode())].concat();
+			let starting_after = starting_after.map(|group_id| <Groups<T>>::hashed_key_for(account, group_id));
+
+			Self::page_after::<u32, bool>(prefix, 8, starting_after, limit)
+				.into_iter()
+				.map(|(group_id, _)| group_id)
+				.collect()
+		}
+
+		/// Members of a group with their liveness, at most `limit` members following `starting_after`.
+		///
+		/// Members are sorted by storage key, a page keeps its place when members join or leave.
+		pub fn group_members(
+			group_id: u32,
+			starting_after: Option<T::AccountId>,
+			limit: u32,
+		) -> Vec<GroupMemberState<T::AccountId, T::BlockNumber>> {
+			let now = frame_system::Pallet::<T>::block_number();
+			let alive_time = Self::group_alive_time(group_id);
+			let prefix = [&<GroupMembers<T>>::final_prefix()[..], &Twox64Concat::hash(&group_id.encode())].concat();
+			let starting_after = starting_after.map(|account| <GroupMembers<T>>::hashed_key_for(group_id, account));
+
+			Self::page_after(prefix, 16, starting_after, limit)
+				.into_iter()
+				.map(|(account, state)| Self::member_state(account, state, now, alive_time))
+				.collect()
+		}
+
+		// Removing account from accounts index, its heartbeats and its leaderships
+		fn remove_node(account: &T::AccountId) -> Weight {
+			let db_weight = T::DbWeight::get();
+			let mut weight = db_weight.reads_writes(3, 4);
+
+			// Compacting accounts index by moving last account into removed account slot
+			let index = Self::get_accounts_index(account);
+			let last_index = Self::get_accounts_count().unwrap_or(0).saturating_sub(1);
+			if index != last_index {
+				let last_account = Self::get_account(last_index);
+				<Accounts<T>>::insert(index, &last_account);
+				<AccountsIndex<T>>::insert(&last_account, index);
+				weight = weight.saturating_add(db_weight.reads_writes(1, 2));
+			}
+			<Accounts<T>>::remove(last_index);
+			<AccountsIndex<T>>::remove(account);
+			<AccountsCount<T>>::put(last_index);
+			<Heartbeats<T>>::remove(account);
+
+			// Removing account from all its groups
+			let groups: Vec<u32> = <Groups<T>>::iter_prefix(account).map(|(group_id, _)| group_id).collect();
+			for group_id in groups {
+				<Groups<T>>::remove(account, group_id);
+				<GroupMembers<T>>::remove(group_id, account);
+				<GroupMembersCount<T>>::mutate(group_id, |count| *count = count.saturating_sub(1));
+				weight = weight.saturating_add(db_weight.reads_writes(2, 3));
+			}
+
+			weight = weight.saturating_add(Self::release_leaderships(account));
+
+			Self::deposit_event(Event::NodeRemoved(account.clone()));
+
+			weight
+		}
+
+		// Upper bound of `remove_node` weight for a node in `groups` groups while `leaders` groups are leaded
+		fn remove_node_weight(groups: u32, leaders: u32) -> Weight {
+			let db_weight = T::DbWeight::get();
+			db_weight.reads_writes(4, 6)
+				.saturating_add(db_weight.reads_writes(2, 3).saturating_mul(groups as Weight))
+				.saturating_add(db_weight.reads_writes(2, 3).saturating_mul(leaders as Weight))
+		}
+
+		// Starting or renewing the lease of a group leader, indexed by its end
+		pub(crate) fn start_lease(group_id: u32, now: T::BlockNumber) {
+			let lease_end = now.saturating_add(Self::group_lease_duration(group_id));
+			<LeaderLease<T>>::insert(group_id, lease_end);
+			<LeaseExpiries<T>>::mutate(lease_end, |groups| {
+				if !groups.contains(&group_id) {
+					groups.push(group_id);
+				}
+			});
+		}
+
+		// First block where a group member with this last heartbeat is not alive anymore
+		fn stale_block(group_id: u32, last_heartbeat: T::BlockNumber) -> T::BlockNumber {
+			last_heartbeat.saturating_add(Self::group_alive_time(group_id)).saturating_add(1u32.into())
+		}
+
+		// Indexing the block where the group leader becomes stale, unless it is already past
+		pub(crate) fn schedule_stale_check(group_id: u32, last_heartbeat: T::BlockNumber, now: T::BlockNumber) {
+			let stale_block = Self::stale_block(group_id, last_heartbeat);
+			if stale_block > now {
+				<StaleChecks<T>>::mutate(stale_block, |groups| {
+					if !groups.contains(&group_id) {
+						groups.push(group_id);
+					}
+				});
+			}
+		}
+
+		// Releasing all groups leaded by account
+		fn release_leaderships(account: &T::AccountId) -> Weight {
+			let leaders: Vec<(u32, T::AccountId)> = <Leaders<T>>::iter().collect();
+			let mut weight = T::DbWeight::get().reads(leaders.len() as Weight);
+
+			for (group_id, _) in leaders.into_iter().filter(|(_, leader)| leader == account) {
+				<Leaders<T>>::remove(group_id);
+				<LeadedGroup<T>>::insert(group_id, false);
+				<LeaderLease<T>>::remove(group_id);
+				Self::deposit_event(Event::GiveUpLeader(account.clone(), group_id, Self::get_leadership_epoch(group_id)));
+				weight = weight.saturating_add(T::DbWeight::get().reads_writes(1, 3));
+			}
+
+			weight
+		}
+
+		// Releasing leadership of a group on behalf of the admin
+		fn force_release(group_id: u32) -> DispatchResult {
+			ensure!(Self::get_leaded_group(group_id), Error::<T>::NoLeader);
+			let leader = <Leaders<T>>::take(group_id);
+			<LeadedGroup<T>>::insert(group_id, false);
+			<LeaderLease<T>>::remove(group_id);
+
+			Self::deposit_event(Event::GiveUpForced(leader, group_id, Self::get_leadership_epoch(group_id)));
+
+			Ok(())
+		}
+
+		// Counting groups of account, reading at most one group more than the `groups` witness
+		fn ensure_groups_witness(account: &T::AccountId, groups: u32) -> Result<u32, DispatchError> {
+			let count = <Groups<T>>::iter_prefix(account).take((groups as usize).saturating_add(1)).count() as u32;
+			ensure!(count <= groups, Error::<T>::InvalidWitness);
+			Ok(count)
+		}
+
+		// Counting leaded groups, reading at most one leader more than the `leaders` witness
+		fn ensure_leaders_witness(leaders: u32) -> Result<u32, DispatchError> {
+			let count = <Leaders<T>>::iter().take((leaders as usize).saturating_add(1)).count() as u32;
+			ensure!(count <= leaders, Error::<T>::InvalidWitness);
+			Ok(count)
+		}
+
+		// Checking that account belongs to the federation
+		fn ensure_member(account: &T::AccountId) -> DispatchResult {
+			ensure!(Self::is_member(account), Error::<T>::NotMember);
+			Ok(())
+		}
+
+		// Checking that origin is the admin origin or the admin of a registered group
+		fn ensure_group_admin(origin: OriginFor<T>, group_id: u32) -> DispatchResult {
+			let info = Self::get_group_info(group_id).ok_or(Error::<T>::UnknownGroup)?;
+			if let Err(origin) = T::AdminOrigin::try_origin(origin) {
+				let sender: T::AccountId = ensure_signed(origin)?;
+				ensure!(info.admin == sender, Error::<T>::NotGroupAdmin);
+			}
+			Ok(())
+		}
+
+		// Building group info from call parameters
+		fn new_group_info(
+			name: Vec<u8>,
+			service: ServiceType,
+			admin: T::AccountId,
+			alive_time: T::BlockNumber,
+			lease_duration: T::BlockNumber,
+		) -> Result<GroupInfoOf<T>, DispatchError> {
+			let name = BoundedVec::try_from(name).map_err(|_| Error::<T>::GroupNameTooLong)?;
+			ensure!(
+				!alive_time.is_zero() && !lease_duration.is_zero(),
+				Error::<T>::InvalidGroupParameters
+			);
+			// Group admin must be a federation member
+			Self::ensure_member(&admin)?;
+
+			Ok(GroupInfo { name, service, admin, alive_time, lease_duration })
+		}
+
+		/// Alive time of a group, runtime default is used for unregistered groups.
+		pub fn group_alive_time(group_id: u32) -> T::BlockNumber {
+			Self::get_group_info(group_id)
+				.map(|info| info.alive_time)
+				.unwrap_or_else(T::AliveTime::get)
+		}
+
+		/// Lease duration of a group, runtime default is used for unregistered groups.
+		pub fn group_lease_duration(group_id: u32) -> T::BlockNumber {
+			Self::get_group_info(group_id)
+				.map(|info| info.lease_duration)
+				.unwrap_or_else(T::LeaseDuration::get)
+		}
+
+		/// Registered groups, at most `limit` groups following `starting_after`.
+		///
+		/// Groups are sorted by storage key, a page keeps its place when groups are registered or removed.
+		pub fn registered_groups(
+			starting_after: Option<u32>,
+			limit: u32,
+		) -> Vec<(u32, GroupInfo<T::AccountId, T::BlockNumber, Vec<u8>>)> {
+			let prefix = <GroupsInfo<T>>::final_prefix().to_vec();
+			let starting_after = starting_after.map(|group_id| <GroupsInfo<T>>::hashed_key_for(group_id));
+
+			Self::page_after::<u32, GroupInfoOf<T>>(prefix, 8, starting_after, limit)
+				.into_iter()
+				.map(|(group_id, info)| (group_id, GroupInfo {
+					name: info.name.to_vec(),
+					service: info.service,
+					admin: info.admin,
+					alive_time: info.alive_time,
+					lease_duration: info.lease_duration,
+				}))
+				.collect()
+		}
+
+		/// Account a node key signs for, keys not set by a member identify their own account.
+		pub fn key_account(public: &T::Public) -> T::AccountId {
+			Self::get_key_owner(public).unwrap_or_else(|| public.clone().into_account())
+		}
+
+		// Mapping archipel key to a member, replacing its previous key
+		fn do_set_keys(account: &T::AccountId, public: &T::Public) -> DispatchResult {
+			if let Some(owner) = Self::get_key_owner(public) {
+				ensure!(owner == *account, Error::<T>::KeyInUse);
+			}
+
+			// Key of another account can't be taken over
+			let key_account = public.clone().into_account();
+			ensure!(key_account == *account || !Self::is_member(&key_account), Error::<T>::KeyInUse);
+
+			Self::remove_keys(account);
+			<Keys<T>>::insert(account, public);
+			<KeyOwner<T>>::insert(public, account);
+
+			Self::deposit_event(Event::KeysSet(account.clone()));
+
+			Ok(())
+		}
+
+		/// Mapping archipel session keys of member validators, keys already used by others are skipped.
+		pub fn rotate_session_keys(validators: impl Iterator<Item = (T::AccountId, T::Public)>) {
+			for (account, public) in validators {
+				if Self::is_member(&account) && Self::get_keys(&account).as_ref() != Some(&public) {
+					let _ = Self::do_set_keys(&account, &public);
+				}
+			}
+		}
+
+		// Removing archipel key of a member
+		fn remove_keys(account: &T::AccountId) -> Option<T::Public> {
+			let public = <Keys<T>>::take(account)?;
+			<KeyOwner<T>>::remove(&public);
+			Some(public)
+		}
 	}
 
 	// Dispatchable functions allows users to interact with the pallet and invoke state changes.
@@ -119,16 +1346,40 @@ pub mod pallet {
 	#[pallet::call]
 	impl<T:Config> Pallet<T> {
 
-		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1,2))]
-		pub fn set_leader(origin: OriginFor<T>, old_leader: T::AccountId, group_id: u32) -> DispatchResult {
+		#[pallet::weight(T::WeightInfo::set_leader())]
+		pub fn set_leader(
+			origin: OriginFor<T>,
+			old_leader: T::AccountId,
+			group_id: u32,
+			epoch: u64,
+			valid_until: Option<T::BlockNumber>,
+		) -> DispatchResult {
 			let sender: T::AccountId = ensure_signed(origin)?;
+			Self::ensure_member(&sender)?;
+			// Only nodes serving the group can lead it
+			ensure!(<GroupMembers<T>>::contains_key(group_id, &sender), Error::<T>::NotGroupMember);
+
+			let now = frame_system::Pallet::<T>::block_number();
+
+			// Checking if request is still valid
+			if let Some(valid_until) = valid_until {
+				ensure!(now <= valid_until, Error::<T>::RequestExpired);
+			}
+
+			// Checking that leadership didn't change since sender observed it
+			let current_epoch = Self::get_leadership_epoch(group_id);
+			ensure!(epoch == current_epoch, Error::<T>::StaleEpoch);
 
 			// If leader is already set by someone in this group
 			if <Leaders<T>>::contains_key(&group_id) {
 				let leader =  Self::get_leader(group_id);
 				// Checking if leader can be set
-				ensure!(sender != old_leader, "You are already leader.");
-				ensure!(old_leader ==  leader, "Incorrect old leader report.");
+				ensure!(sender != old_leader, Error::<T>::AlreadyLeader);
+				ensure!(old_leader ==  leader, Error::<T>::IncorrectOldLeader);
+
+				// Checking if leader stopped sending heartbeats
+				let last_heartbeat = Self::get_group_heartbeat(&leader, group_id);
+				ensure!(now.saturating_sub(last_heartbeat) > Self::group_alive_time(group_id), Error::<T>::LeaderStillAlive);
 			}
 
             // Updating leader for group id
@@ -136,58 +1387,394 @@ pub mod pallet {
 
 			<LeadedGroup<T>>::insert(group_id, true);
 
+			// Starting leader lease
+			Self::start_lease(group_id, now);
+			Self::schedule_stale_check(group_id, Self::get_group_heartbeat(&sender, group_id), now);
+
+			// Starting new leadership epoch
+			let new_epoch = current_epoch.saturating_add(1);
+			<LeadershipEpochs<T>>::insert(group_id, new_epoch);
+
             // Triggering leader update event
-            Self::deposit_event(Event::NewLeader(sender, group_id));
+            Self::deposit_event(Event::NewLeader(sender, group_id, new_epoch));
 
             Ok(())
 		}
 
-		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2,2))]
+		#[pallet::weight(T::WeightInfo::give_up_leadership())]
 		pub fn give_up_leadership(origin: OriginFor<T>, group_id: u32) -> DispatchResult {
 
 			let sender: T::AccountId = ensure_signed(origin)?;
+			Self::ensure_member(&sender)?;
 
 			let leaded_group = Self::get_leaded_group(group_id);
 
-			ensure!(leaded_group ==  true, "No Leader in this group.");
+			ensure!(leaded_group ==  true, Error::<T>::NoLeader);
 
 			let leader =  Self::get_leader(group_id);
 		
-			ensure!(leader ==  sender, "You are not the current leader.");
+			ensure!(leader ==  sender, Error::<T>::NotLeader);
 
 			<LeadedGroup<T>>::insert(group_id, false);
 
 			<Leaders<T>>::remove(group_id);
 
-			Self::deposit_event(Event::GiveUpLeader(sender, group_id));
+			<LeaderLease<T>>::remove(group_id);
+
+			Self::deposit_event(Event::GiveUpLeader(sender, group_id, Self::get_leadership_epoch(group_id)));
 
 			Ok(())
 		}
 
-		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2,6))]
+		#[pallet::weight(T::WeightInfo::add_heartbeat_first())]
         // Add hearthbeats
-        pub fn add_heartbeat(origin: OriginFor<T>, group_id: u32, node_status: u32) -> DispatchResult {
+        pub fn add_heartbeat(origin: OriginFor<T>, group_id: u32, node_status: NodeStatus) -> DispatchResultWithPostInfo {
+			let sender: T::AccountId = ensure_signed(origin)?;
+			Self::ensure_member(&sender)?;
+
+			Self::record_heartbeat(&sender, group_id, node_status)
+        }
+
+		#[pallet::weight(T::WeightInfo::add_heartbeats(heartbeats.len() as u32))]
+		// Add hearthbeats for several groups at once
+		pub fn add_heartbeats(origin: OriginFor<T>, heartbeats: Vec<(u32, NodeStatus)>) -> DispatchResult {
 			let sender: T::AccountId = ensure_signed(origin)?;
+			Self::ensure_member(&sender)?;
 
 			let now = frame_system::Pallet::<T>::block_number();
-	
-            // Adding account in map
-            Self::add_account(&sender)?;
 
-            // Adding sender into groups map
-            <Groups<T>>::insert(&sender, group_id);
+			// Adding account in map
+			Self::add_account(&sender)?;
 
-            // Adding Now into Heartbeats map
+			// Adding Now into Heartbeats map
 			<Heartbeats<T>>::insert(&sender, now);
-			
-			// Adding node status into NodesStatus map
-			<NodesStatus<T>>::insert(&sender, node_status);
 
-            // Triggering heartbeats update event
-            Self::deposit_event(Event::NewHeartbeat(sender, group_id, node_status, now));
+			for (group_id, node_status) in heartbeats {
+				Self::do_heartbeat(&sender, group_id, node_status, now);
+			}
 
-            Ok(())
-        }
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::heartbeat())]
+		// Add heartbeat without fees, checked by `validate_unsigned`
+		pub fn heartbeat(
+			origin: OriginFor<T>,
+			heartbeat: Heartbeat<T::Public, T::BlockNumber>,
+			_signature: T::Signature,
+		) -> DispatchResultWithPostInfo {
+			ensure_none(origin)?;
+			let account = Self::key_account(&heartbeat.public);
+			Self::ensure_member(&account)?;
+
+			// Repeated heartbeats are refunded down to a regular heartbeat plus key and membership reads
+			let post_info = Self::record_heartbeat(&account, heartbeat.group_id, heartbeat.status)?;
+			Ok(post_info.actual_weight.map(|weight| weight.saturating_add(T::DbWeight::get().reads(2))).into())
+		}
+
+		#[pallet::weight(T::WeightInfo::add_member())]
+		pub fn add_member(origin: OriginFor<T>, member: T::AccountId) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			ensure!(!Self::is_member(&member), Error::<T>::AlreadyMember);
 
+			<Members<T>>::insert(&member, true);
+
+			Self::deposit_event(Event::MemberAdded(member));
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::remove_member(*groups, *leaders))]
+		// Remove a member from the federation with its node, `groups` and `leaders` are upper bounds of its groups and of leaded groups counts
+		pub fn remove_member(
+			origin: OriginFor<T>,
+			member: T::AccountId,
+			groups: u32,
+			leaders: u32,
+		) -> DispatchResultWithPostInfo {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			Self::ensure_member(&member)?;
+			let groups = Self::ensure_groups_witness(&member, groups)?;
+			let leaders = Self::ensure_leaders_witness(leaders)?;
+
+			<Members<T>>::remove(&member);
+
+			// A removed member can't keep serving or leading any group
+			if <AccountsIndex<T>>::contains_key(&member) {
+				Self::remove_node(&member);
+			} else {
+				Self::release_leaderships(&member);
+			}
+
+			// Key of a removed member can be set by another member
+			Self::remove_keys(&member);
+
+			Self::deposit_event(Event::MemberRemoved(member));
+
+			Ok(Some(T::WeightInfo::remove_member(groups, leaders)).into())
+		}
+
+		#[pallet::weight(T::WeightInfo::deregister(*groups, *leaders))]
+		// Remove sender node, `groups` and `leaders` are upper bounds of its groups and of leaded groups counts
+		pub fn deregister(origin: OriginFor<T>, groups: u32, leaders: u32) -> DispatchResultWithPostInfo {
+			let sender: T::AccountId = ensure_signed(origin)?;
+
+			ensure!(<AccountsIndex<T>>::contains_key(&sender), Error::<T>::UnknownNode);
+			let groups = Self::ensure_groups_witness(&sender, groups)?;
+			let leaders = Self::ensure_leaders_witness(leaders)?;
+
+			Self::remove_node(&sender);
+
+			Ok(Some(T::WeightInfo::deregister(groups, leaders)).into())
+		}
+
+		#[pallet::weight(T::WeightInfo::force_remove(*groups, *leaders))]
+		// Remove a node, `groups` and `leaders` are upper bounds of its groups and of leaded groups counts
+		pub fn force_remove(
+			origin: OriginFor<T>,
+			node: T::AccountId,
+			groups: u32,
+			leaders: u32,
+		) -> DispatchResultWithPostInfo {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			ensure!(<AccountsIndex<T>>::contains_key(&node), Error::<T>::UnknownNode);
+			let groups = Self::ensure_groups_witness(&node, groups)?;
+			let leaders = Self::ensure_leaders_witness(leaders)?;
+
+			Self::remove_node(&node);
+
+			Ok(Some(T::WeightInfo::force_remove(groups, leaders)).into())
+		}
+
+		#[pallet::weight(T::WeightInfo::create_group(name.len() as u32))]
+		// Register group parameters, restricted to the admin as they drive leadership of group members
+		pub fn create_group(
+			origin: OriginFor<T>,
+			group_id: u32,
+			name: Vec<u8>,
+			service: ServiceType,
+			admin: T::AccountId,
+			alive_time: T::BlockNumber,
+			lease_duration: T::BlockNumber,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			ensure!(!<GroupsInfo<T>>::contains_key(group_id), Error::<T>::GroupAlreadyExists);
+
+			let info = Self::new_group_info(name, service, admin.clone(), alive_time, lease_duration)?;
+			<GroupsInfo<T>>::insert(group_id, info);
+
+			Self::deposit_event(Event::GroupCreated(group_id, admin));
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::update_group(name.len() as u32))]
+		// Update group parameters or hand the group over to another admin
+		pub fn update_group(
+			origin: OriginFor<T>,
+			group_id: u32,
+			name: Vec<u8>,
+			service: ServiceType,
+			admin: T::AccountId,
+			alive_time: T::BlockNumber,
+			lease_duration: T::BlockNumber,
+		) -> DispatchResult {
+			Self::ensure_group_admin(origin, group_id)?;
+
+			let info = Self::new_group_info(name, service, admin, alive_time, lease_duration)?;
+			<GroupsInfo<T>>::insert(group_id, info);
+
+			// Leader liveness is checked against the new alive time
+			if <LeaderLease<T>>::contains_key(group_id) {
+				let last_heartbeat = Self::get_group_heartbeat(&Self::get_leader(group_id), group_id);
+				Self::schedule_stale_check(group_id, last_heartbeat, frame_system::Pallet::<T>::block_number());
+			}
+
+			Self::deposit_event(Event::GroupUpdated(group_id));
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::remove_group())]
+		pub fn remove_group(origin: OriginFor<T>, group_id: u32) -> DispatchResult {
+			Self::ensure_group_admin(origin, group_id)?;
+
+			// Group falls back to runtime default parameters
+			<GroupsInfo<T>>::remove(group_id);
+
+			Self::deposit_event(Event::GroupRemoved(group_id));
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::force_set_leader())]
+		// Set group leader without leadership checks to recover a stuck group
+		pub fn force_set_leader(origin: OriginFor<T>, group_id: u32, leader: T::AccountId) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			Self::ensure_member(&leader)?;
+			ensure!(Self::is_in_group(&leader, group_id), Error::<T>::NotGroupMember);
+
+			let now = frame_system::Pallet::<T>::block_number();
+			<Leaders<T>>::insert(group_id, &leader);
+			<LeadedGroup<T>>::insert(group_id, true);
+			Self::start_lease(group_id, now);
+			Self::schedule_stale_check(group_id, Self::get_group_heartbeat(&leader, group_id), now);
+
+			// Orchestrators holding the previous epoch can't take leadership back
+			let new_epoch = Self::get_leadership_epoch(group_id).saturating_add(1);
+			<LeadershipEpochs<T>>::insert(group_id, new_epoch);
+
+			Self::deposit_event(Event::LeaderForced(leader, group_id, new_epoch));
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::force_give_up())]
+		pub fn force_give_up(origin: OriginFor<T>, group_id: u32) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			Self::force_release(group_id)
+		}
+
+		#[pallet::weight(T::WeightInfo::force_set_status())]
+		// Set status of a group member, keeping its last heartbeat
+		pub fn force_set_status(
+			origin: OriginFor<T>,
+			node: T::AccountId,
+			group_id: u32,
+			status: NodeStatus,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			<GroupMembers<T>>::try_mutate(group_id, &node, |state| -> DispatchResult {
+				let state = state.as_mut().ok_or(Error::<T>::UnknownNode)?;
+				state.status = status;
+				Ok(())
+			})?;
+
+			Self::deposit_event(Event::StatusForced(node, group_id, status));
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::force_clear_group(*members))]
+		// Remove all members and the leadership of a group, `members` is an upper bound of group members count
+		pub fn force_clear_group(origin: OriginFor<T>, group_id: u32, members: u32) -> DispatchResultWithPostInfo {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			let count = Self::get_group_members_count(group_id);
+			ensure!(count <= members, Error::<T>::InvalidWitness);
+
+			if Self::get_leaded_group(group_id) {
+				Self::force_release(group_id)?;
+			}
+
+			// Leadership epoch is kept so that cleared leaderships can't be replayed
+			for (account, _) in <GroupMembers<T>>::drain_prefix(group_id) {
+				<Groups<T>>::remove(&account, group_id);
+			}
+			<GroupMembersCount<T>>::remove(group_id);
+
+			Self::deposit_event(Event::GroupCleared(group_id, count));
+
+			Ok(Some(T::WeightInfo::force_clear_group(count)).into())
+		}
+
+		#[pallet::weight(T::WeightInfo::set_keys())]
+		// Set archipel key signing heartbeats of the member, replacing its previous key.
+		// `signature` is a `KeysProof` for the sender and its current keys nonce signed with the key.
+		pub fn set_keys(origin: OriginFor<T>, public: T::Public, signature: T::Signature) -> DispatchResult {
+			let sender: T::AccountId = ensure_signed(origin)?;
+			Self::ensure_member(&sender)?;
+
+			let nonce = Self::keys_nonce(&sender);
+			let proof = KeysProof { public: public.clone(), account: sender.clone(), nonce };
+			ensure!(
+				SignedPayload::<T>::verify::<T::AuthorityId>(&proof, signature),
+				Error::<T>::InvalidKeysProof
+			);
+
+			Self::do_set_keys(&sender, &public)?;
+			<KeysNonce<T>>::insert(&sender, nonce.saturating_add(1));
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::purge_keys())]
+		pub fn purge_keys(origin: OriginFor<T>) -> DispatchResult {
+			let sender: T::AccountId = ensure_signed(origin)?;
+
+			ensure!(Self::remove_keys(&sender).is_some(), Error::<T>::NoKeys);
+
+			Self::deposit_event(Event::KeysPurged(sender));
+
+			Ok(())
+		}
+	}
+
+	/// Invalid unsigned heartbeat sent by an account outside of the federation.
+	pub const INVALID_MEMBER: u8 = 1;
+
+	/// Invalid unsigned heartbeat sent for a group that is not registered.
+	pub const INVALID_GROUP: u8 = 2;
+
+	/// Invalid unsigned heartbeat sent by a member that did not join the group with a signed heartbeat.
+	pub const INVALID_GROUP_MEMBER: u8 = 3;
+
+	#[pallet::validate_unsigned]
+	impl<T: Config> ValidateUnsigned for Pallet<T> {
+		type Call = Call<T>;
+
+		fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+			let (heartbeat, signature) = match call {
+				Call::heartbeat(heartbeat, signature) => (heartbeat, signature),
+				_ => return InvalidTransaction::Call.into(),
+			};
+
+			// Heartbeats are valid until they can't prove liveness anymore
+			let now = frame_system::Pallet::<T>::block_number();
+			if heartbeat.block_number > now {
+				return InvalidTransaction::Future.into();
+			}
+			let alive_time = Self::group_alive_time(heartbeat.group_id);
+			let age = now.saturating_sub(heartbeat.block_number);
+			if age >= alive_time {
+				return InvalidTransaction::Stale.into();
+			}
+
+			let account = Self::key_account(&heartbeat.public);
+			if !Self::is_member(&account) {
+				return InvalidTransaction::Custom(INVALID_MEMBER).into();
+			}
+
+			// Fee-less heartbeats only keep alive members of registered groups
+			if !<GroupsInfo<T>>::contains_key(heartbeat.group_id) {
+				return InvalidTransaction::Custom(INVALID_GROUP).into();
+			}
+			let last_heartbeat = match Self::get_group_member(heartbeat.group_id, &account) {
+				Some(state) => state.last_heartbeat,
+				None => return InvalidTransaction::Custom(INVALID_GROUP_MEMBER).into(),
+			};
+
+			// A single heartbeat is accepted per node and group until a newer one is created
+			if last_heartbeat >= heartbeat.block_number {
+				return InvalidTransaction::Stale.into();
+			}
+
+			if !SignedPayload::<T>::verify::<T::AuthorityId>(heartbeat, signature.clone()) {
+				return InvalidTransaction::BadProof.into();
+			}
+
+			ValidTransaction::with_tag_prefix("ArchipelHeartbeat")
+				.priority(T::UnsignedPriority::get())
+				.and_provides((account, heartbeat.group_id))
+				.longevity(TryInto::<u64>::try_into(alive_time.saturating_sub(age)).unwrap_or(64_u64))
+				.propagate(true)
+				.build()
+		}
 	}
 }
@@ -0,0 +1,147 @@
+//! Storage migrations for pallet-archipel
+
+use super::*;
+use frame_support::{
+	storage::migration::{remove_storage_prefix, storage_iter, storage_key_iter},
+	traits::{Get, PalletInfoAccess},
+	weights::Weight,
+	Twox64Concat,
+};
+use sp_std::{collections::btree_map::BTreeMap, prelude::*};
+
+/// Migration from the first deployed layout to `Releases::V2_0_0`.
+///
+/// Account keyed maps move from `Twox64Concat` to `Blake2_128Concat` and single group
+/// membership with node status moves into group members. Nodes serving a group become
+/// federation members along with accounts listed in `Config::MigrationMembers`.
+pub mod v2 {
+	use super::*;
+
+	// Decoding a `Twox64Concat` key part, skipping its hash
+	fn decode_twox64_concat<K: Decode>(key: &mut &[u8]) -> Option<K> {
+		if key.len() < 8 {
+			return None;
+		}
+		*key = &key[8..];
+		K::decode(key).ok()
+	}
+
+	// Converting node status stored as integer by first releases
+	fn node_status(status: u32) -> NodeStatus {
+		match status {
+			1 => NodeStatus::Active,
+			2 => NodeStatus::Passive,
+			3 => NodeStatus::Maintenance,
+			_ => NodeStatus::Unknown,
+		}
+	}
+
+	pub fn migrate<T: Config>() -> Weight {
+		let pallet = <Pallet<T>>::name().as_bytes();
+		let mut reads: Weight = 1;
+		let mut writes: Weight = 1;
+
+		// Collecting all old entries before rewriting maps under the same prefixes
+		let heartbeats: BTreeMap<T::AccountId, T::BlockNumber> =
+			storage_key_iter::<T::AccountId, T::BlockNumber, Twox64Concat>(pallet, b"Heartbeats").collect();
+		let accounts_index: Vec<(T::AccountId, u32)> =
+			storage_key_iter::<T::AccountId, u32, Twox64Concat>(pallet, b"AccountsIndex").collect();
+		let nodes_status: BTreeMap<T::AccountId, u32> =
+			storage_key_iter::<T::AccountId, u32, Twox64Concat>(pallet, b"NodesStatus").collect();
+		let groups: Vec<(T::AccountId, u32)> = storage_iter::<u32>(pallet, b"Groups")
+			.filter_map(|(key, group_id)| {
+				decode_twox64_concat::<T::AccountId>(&mut &key[..]).map(|account| (account, group_id))
+			})
+			.collect();
+		reads = reads.saturating_add(
+			(heartbeats.len() + accounts_index.len() + nodes_status.len() + groups.len()) as Weight
+		);
+
+		remove_storage_prefix(pallet, b"Heartbeats", &[]);
+		remove_storage_prefix(pallet, b"AccountsIndex", &[]);
+		remove_storage_prefix(pallet, b"NodesStatus", &[]);
+		remove_storage_prefix(pallet, b"Groups", &[]);
+		writes = writes.saturating_add(4);
+
+		for (account, block_number) in heartbeats.iter() {
+			<Heartbeats<T>>::insert(account, block_number);
+		}
+		for (account, index) in accounts_index.iter() {
+			<AccountsIndex<T>>::insert(account, index);
+		}
+		writes = writes.saturating_add((heartbeats.len() + accounts_index.len()) as Weight);
+
+		// Nodes keep their only group with their last heartbeat and status
+		for (account, group_id) in groups.iter() {
+			let status = nodes_status.get(account).map(|status| node_status(*status)).unwrap_or_default();
+			let last_heartbeat = heartbeats.get(account).cloned().unwrap_or_default();
+
+			<Pallet<T>>::insert_group_member(account, *group_id, MemberState { last_heartbeat, status });
+			reads = reads.saturating_add(1);
+			writes = writes.saturating_add(3);
+		}
+
+		// Nodes serving a group keep working as members, listed accounts are added to them
+		for account in groups.iter().map(|(account, _)| account.clone()).chain(T::MigrationMembers::get()) {
+			<Members<T>>::insert(account, true);
+			writes = writes.saturating_add(1);
+		}
+
+		// Current leaders must renew their leadership from now on
+		let now = frame_system::Pallet::<T>::block_number();
+		for (group_id, leader) in <Leaders<T>>::iter() {
+			reads = reads.saturating_add(4);
+			if <LeadedGroup<T>>::get(group_id) && !<LeaderLease<T>>::contains_key(group_id) {
+				<Pallet<T>>::start_lease(group_id, now);
+				<Pallet<T>>::schedule_stale_check(group_id, <Pallet<T>>::get_group_heartbeat(&leader, group_id), now);
+				writes = writes.saturating_add(3);
+			}
+		}
+
+		<StorageVersion<T>>::put(Releases::V2_0_0);
+
+		T::DbWeight::get().reads_writes(reads, writes)
+	}
+
+	#[cfg(feature = "try-runtime")]
+	pub fn pre_migrate<T: Config>() -> Result<(), &'static str> {
+		frame_support::ensure!(
+			<StorageVersion<T>>::get() == Releases::V1_0_0,
+			"Storage version must be V1_0_0 before migration"
+		);
+		Ok(())
+	}
+
+	#[cfg(feature = "try-runtime")]
+	pub fn post_migrate<T: Config>() -> Result<(), &'static str> {
+		frame_support::ensure!(
+			<StorageVersion<T>>::get() == Releases::V2_0_0,
+			"Storage version must be V2_0_0 after migration"
+		);
+
+		// Every known account must be readable with new hashers
+		for (index, account) in <Accounts<T>>::iter() {
+			frame_support::ensure!(
+				<AccountsIndex<T>>::try_get(&account) == Ok(index),
+				"Accounts index must match accounts after migration"
+			);
+		}
+
+		// Group members and listed accounts are members
+		for (_, account, _) in <GroupMembers<T>>::iter() {
+			frame_support::ensure!(<Members<T>>::get(&account), "Group members must be members after migration");
+		}
+		for account in T::MigrationMembers::get() {
+			frame_support::ensure!(<Members<T>>::get(&account), "Listed accounts must be members after migration");
+		}
+
+		// Group members count must match group members
+		for (group_id, count) in <GroupMembersCount<T>>::iter() {
+			frame_support::ensure!(
+				<GroupMembers<T>>::iter_prefix(group_id).count() as u32 == count,
+				"Group members count must match group members after migration"
+			);
+		}
+		Ok(())
+	}
+}
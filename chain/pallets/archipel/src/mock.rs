@@ -1,8 +1,9 @@
 use crate as pallet_archipel;
 use sp_core::H256;
-use frame_support::parameter_types;
+use frame_support::{parameter_types, traits::{GenesisBuild, OnInitialize}, weights::RuntimeDbWeight};
 use sp_runtime::{
-	traits::{BlakeTwo256, IdentityLookup}, testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+	testing::{Header, TestSignature, TestXt, UintAuthorityId},
 };
 use frame_system as system;
 
@@ -17,20 +18,28 @@ frame_support::construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
-		ArchipelModule: pallet_archipel::{Pallet, Call, Storage, Event<T>},
+		ArchipelModule: pallet_archipel::{Pallet, Call, Config<T>, Storage, Event<T>, ValidateUnsigned},
 	}
 );
 
 parameter_types! {
 	pub const BlockHashCount: u64 = 250;
 	pub const SS58Prefix: u8 = 42;
+	pub const AliveTime: u64 = 12;
+	pub const LeaseDuration: u64 = 20;
+	pub const MaxGroupNameLength: u32 = 16;
+	pub const PruneAfter: u64 = 100;
+	pub const HeartbeatInterval: u64 = 5;
+	pub const UnsignedPriority: u64 = 1 << 20;
+	pub const TestDbWeight: RuntimeDbWeight = RuntimeDbWeight { read: 100, write: 1000 };
+	pub MigrationMembers: Vec<u64> = vec![50];
 }
 
 impl system::Config for Test {
 	type BaseCallFilter = ();
 	type BlockWeights = ();
 	type BlockLength = ();
-	type DbWeight = ();
+	type DbWeight = TestDbWeight;
 	type Origin = Origin;
 	type Call = Call;
 	type Index = u64;
@@ -52,11 +61,64 @@ impl system::Config for Test {
 	type OnSetCode = ();
 }
 
+pub type Extrinsic = TestXt<Call, ()>;
+
+impl system::offchain::SigningTypes for Test {
+	type Public = UintAuthorityId;
+	type Signature = TestSignature;
+}
+
+impl<LocalCall> system::offchain::SendTransactionTypes<LocalCall> for Test where
+	Call: From<LocalCall>,
+{
+	type OverarchingCall = Call;
+	type Extrinsic = Extrinsic;
+}
+
+// Test keys signing offchain heartbeats, accounts are key numbers.
+pub struct TestAuthId;
+
+impl system::offchain::AppCrypto<UintAuthorityId, TestSignature> for TestAuthId {
+	type RuntimeAppPublic = UintAuthorityId;
+	type GenericSignature = TestSignature;
+	type GenericPublic = UintAuthorityId;
+}
+
 impl pallet_archipel::Config for Test {
 	type Event = Event;
+	type AuthorityId = TestAuthId;
+	type AdminOrigin = system::EnsureRoot<u64>;
+	type AliveTime = AliveTime;
+	type LeaseDuration = LeaseDuration;
+	type MaxGroupNameLength = MaxGroupNameLength;
+	type PruneAfter = PruneAfter;
+	type HeartbeatInterval = HeartbeatInterval;
+	type MigrationMembers = MigrationMembers;
+	type UnsignedPriority = UnsignedPriority;
+	type WeightInfo = ();
 }
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-	system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
-}
\ No newline at end of file
+	new_test_ext_with_groups(vec![], vec![])
+}
+
+// Build genesis storage with initial groups and leaders.
+pub fn new_test_ext_with_groups(groups: Vec<(u64, u32)>, leaders: Vec<(u32, u64)>) -> sp_io::TestExternalities {
+	let mut storage = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_archipel::GenesisConfig::<Test> {
+		// Accounts 10, 20 and 30 are federation members
+		members: vec![10, 20, 30],
+		groups,
+		leaders,
+	}.assimilate_storage(&mut storage).unwrap();
+	storage.into()
+}
+
+// Run pallet hooks until reaching block number n.
+pub fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		System::set_block_number(System::block_number() + 1);
+		ArchipelModule::on_initialize(System::block_number());
+	}
+}
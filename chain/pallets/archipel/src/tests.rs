@@ -1,11 +1,35 @@
-use crate::{mock::*};
-use frame_support::{assert_ok, assert_noop};
+use crate::{
+	ActionReason, Error, GroupEvent, Heartbeat, KeysProof, MemberState, NodeStatus, RecommendedAction, Releases, ServiceType,
+	INVALID_GROUP, INVALID_GROUP_MEMBER, INVALID_MEMBER, OFFCHAIN_GROUP_KEY, OFFCHAIN_STATUS_KEY, mock::*, weights::WeightInfo,
+};
+use codec::{Decode, Encode};
+use frame_support::{
+	assert_ok, assert_noop,
+	storage::migration::{get_storage_value, put_storage_value},
+	traits::{OffchainWorker, OnIdle, OnRuntimeUpgrade, PalletInfoAccess},
+	unsigned::ValidateUnsigned,
+	StorageHasher, Twox64Concat,
+};
+use sp_core::offchain::{
+	testing::{TestOffchainExt, TestTransactionPoolExt},
+	OffchainDbExt, OffchainWorkerExt, StorageKind, TransactionPoolExt,
+};
+use sp_runtime::{
+	testing::{TestSignature, UintAuthorityId},
+	transaction_validity::{InvalidTransaction, TransactionSource, TransactionValidityError},
+	DispatchError, RuntimeAppPublic,
+};
+
+fn has_event(event: crate::Event<Test>) -> bool {
+	System::events().iter().any(|record| record.event == Event::pallet_archipel(event.clone()))
+}
 
 #[test]
 fn set_leader_should_work() {
 	new_test_ext().execute_with(|| {
 		// set leader
-		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
 
 		// check if leader was correctly set in group 1
 		assert_eq!(ArchipelModule::get_leader(1), 10);
@@ -17,12 +41,13 @@ fn set_leader_should_work() {
 fn wrong_old_leader_should_fail() {
 	new_test_ext().execute_with(|| {
 		// set leader
-		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
 
 		// wrong old leader
 		assert_noop!(
-			ArchipelModule::set_leader(Origin::signed(10), 0, 1),
-			"Incorrect old leader report."
+			ArchipelModule::set_leader(Origin::signed(10), 0, 1, 1, None),
+			Error::<Test>::IncorrectOldLeader
 		);
 	})
 }
@@ -31,12 +56,13 @@ fn wrong_old_leader_should_fail() {
 fn me_old_leader_should_fail() {
 	new_test_ext().execute_with(|| {
 		// set leader
-		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
 
 		// the same old leader
 		assert_noop!(
-			ArchipelModule::set_leader(Origin::signed(10), 10, 1),
-			"You are already leader."
+			ArchipelModule::set_leader(Origin::signed(10), 10, 1, 1, None),
+			Error::<Test>::AlreadyLeader
 		);
 	})
 }
@@ -44,24 +70,75 @@ fn me_old_leader_should_fail() {
 #[test]
 fn change_leader_should_work() {
 	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+
 		// set leader
-		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
+
+		// leader stops sending heartbeats for more than AliveTime blocks
+		System::set_block_number(1 + AliveTime::get() + 1);
 
 		// change leader
-		assert_ok!(ArchipelModule::set_leader(Origin::signed(20), 10, 1));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(20), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(20), 10, 1, 1, None));
 
 		// check if leader was correctly changed
 		assert_eq!(ArchipelModule::get_leader(1), 20);
 	})
 }
 
+#[test]
+fn alive_leader_takeover_should_fail() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+
+		// set leader
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
+
+		// last leader heartbeat is exactly AliveTime blocks old
+		System::set_block_number(1 + AliveTime::get());
+
+		// leader is still considered alive
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(20), 1, NodeStatus::Active));
+		assert_noop!(
+			ArchipelModule::set_leader(Origin::signed(20), 10, 1, 1, None),
+			Error::<Test>::LeaderStillAlive
+		);
+		assert_eq!(ArchipelModule::get_leader(1), 10);
+	})
+}
+
+#[test]
+fn leader_heartbeat_should_prevent_takeover() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+
+		// set leader
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
+
+		// leader keeps sending heartbeats
+		System::set_block_number(10);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		System::set_block_number(20);
+
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(20), 1, NodeStatus::Active));
+		assert_noop!(
+			ArchipelModule::set_leader(Origin::signed(20), 10, 1, 1, None),
+			Error::<Test>::LeaderStillAlive
+		);
+	})
+}
+
 #[test]
 fn add_heartbeat_should_work() {
 	new_test_ext().execute_with(|| {
 
 		System::set_block_number(42);
 		// add heartbeat
-		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, 2));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Passive));
 
 		// checking if account was successfully added to structures
 		assert_eq!(ArchipelModule::get_accounts_count().unwrap_or(0), 1);
@@ -69,13 +146,14 @@ fn add_heartbeat_should_work() {
 		assert_eq!(ArchipelModule::get_account(0), 10);
 
 		// check user group
-		assert_eq!(ArchipelModule::get_group(10), 1);
+		assert!(ArchipelModule::is_in_group(10, 1));
 
 		// check node status 
-		assert_eq!(ArchipelModule::get_node_status(10), 2);
+		assert_eq!(ArchipelModule::get_node_status(&10, 1), NodeStatus::Passive);
 
 		// check heartbeats blockNumber
 		assert_eq!(ArchipelModule::get_heartbeat(10), 42);
+		assert_eq!(ArchipelModule::get_group_heartbeat(&10, 1), 42);
 
 	})
 }
@@ -86,21 +164,21 @@ fn update_heartbeat_should_work() {
 		System::set_block_number(42);
 
 		// set heartbeat 1
-		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, 2));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Passive));
 
 		// check heartbeats blockNumber
 		assert_eq!(ArchipelModule::get_heartbeat(10), 42);
 
 		// check user group
-		assert_eq!(ArchipelModule::get_group(10), 1);
+		assert!(ArchipelModule::is_in_group(10, 1));
 
 		// check node status 
-		assert_eq!(ArchipelModule::get_node_status(10), 2);
+		assert_eq!(ArchipelModule::get_node_status(&10, 1), NodeStatus::Passive);
 
 		System::set_block_number(43);
 
 		// set heartbeat 2
-		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 3, 4));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 3, NodeStatus::Maintenance));
 
 		// checking if account structure was not altered
 		assert_eq!(ArchipelModule::get_accounts_count().unwrap_or(0), 1);
@@ -110,19 +188,36 @@ fn update_heartbeat_should_work() {
 		// checking updated heartbeat
 		assert_eq!(ArchipelModule::get_heartbeat(10), 43);
 
-		// check user group
-		assert_eq!(ArchipelModule::get_group(10), 3);
+		// check user groups
+		assert!(ArchipelModule::is_in_group(10, 3));
+		assert!(ArchipelModule::is_in_group(10, 1));
 
-		// check node status 
-		assert_eq!(ArchipelModule::get_node_status(10), 4);
+		// check node status of each group
+		assert_eq!(ArchipelModule::get_node_status(&10, 3), NodeStatus::Maintenance);
+		assert_eq!(ArchipelModule::get_node_status(&10, 1), NodeStatus::Passive);
+		assert_eq!(ArchipelModule::get_group_heartbeat(&10, 1), 42);
+		assert_eq!(ArchipelModule::get_group_heartbeat(&10, 3), 43);
 	})
 }
 
+#[test]
+fn node_status_should_be_validated() {
+	// node status codes used by orchestrators
+	assert_eq!(NodeStatus::decode(&mut &[0u8][..]).ok(), Some(NodeStatus::Unknown));
+	assert_eq!(NodeStatus::decode(&mut &[1u8][..]).ok(), Some(NodeStatus::Active));
+	assert_eq!(NodeStatus::decode(&mut &[2u8][..]).ok(), Some(NodeStatus::Passive));
+	assert_eq!(NodeStatus::decode(&mut &[3u8][..]).ok(), Some(NodeStatus::Maintenance));
+
+	// unknown node status code is rejected
+	assert!(NodeStatus::decode(&mut &[4u8][..]).is_err());
+}
+
 #[test]
 fn give_up_leadership_should_work() {
 	new_test_ext().execute_with(|| {
 		// set leader
-		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
 
 		// check if leader was correctly set in group 1
 		assert_eq!(ArchipelModule::get_leader(1), 10);
@@ -136,7 +231,8 @@ fn give_up_leadership_should_work() {
 fn give_up_leadership_on_wrong_group_should_fail() {
 	new_test_ext().execute_with(|| {
 		// set leader
-		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
 
 		// check if leader was correctly set in group 1
 		assert_eq!(ArchipelModule::get_leader(1), 10);
@@ -144,7 +240,7 @@ fn give_up_leadership_on_wrong_group_should_fail() {
 		// wrong group leader
 		assert_noop!(
 			ArchipelModule::give_up_leadership(Origin::signed(10), 2),
-			"No Leader in this group."
+			Error::<Test>::NoLeader
 		);
 	})
 }
@@ -153,7 +249,8 @@ fn give_up_leadership_on_wrong_group_should_fail() {
 fn give_up_leadership_if_not_leader_should_fail() {
 	new_test_ext().execute_with(|| {
 		// set leader
-		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
 
 		// check if leader was correctly set in group 1
 		assert_eq!(ArchipelModule::get_leader(1), 10);
@@ -161,7 +258,7 @@ fn give_up_leadership_if_not_leader_should_fail() {
 		// wrong group leader
 		assert_noop!(
 			ArchipelModule::give_up_leadership(Origin::signed(20), 1),
-			"You are not the current leader."
+			Error::<Test>::NotLeader
 		);
 	})
 }
@@ -169,51 +266,1683 @@ fn give_up_leadership_if_not_leader_should_fail() {
 #[test]
 fn set_leader_event_should_work() {
 	new_test_ext().execute_with(|| {
-		// set leader
-		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1));
+		// events are not stored at genesis block
+		System::set_block_number(1);
 
-		// construct event that should be emitted in the method call directly above
-		//let expected_event = TestEvent::generic_event(Event::NewLeader(10,1));
+		// set leader
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
 
-		// iterate through array of `EventRecord`s
-		//assert!(System::events().iter().any(|a| a.event == expected_event));
+		// check that event was emitted in the method call directly above
+		assert!(has_event(crate::Event::NewLeader(10, 1, 1)));
 	})
 }
 
 #[test]
 fn set_earthbeat_event_should_work() {
 	new_test_ext().execute_with(|| {
+		System::set_block_number(42);
 
 		// Add heartbeat
-		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, 2));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Passive));
 
-		// construct event that should be emitted in the method call directly above
-		// blockNumber is set to 42 in test
-		//let expected_event = TestEvent::generic_event(Event::NewHeartbeat(10, 1, 2, 42));
-
-		// iterate through array of `EventRecord`s
-		//assert!(System::events().iter().any(|a| a.event == expected_event));
+		// check that event was emitted in the method call directly above
+		assert!(has_event(crate::Event::NewHeartbeat(10, 1, NodeStatus::Passive, 42)));
 	})
 }
 
 #[test]
 fn give_up_leadership_event_should_work() {
 	new_test_ext().execute_with(|| {
-	
+		System::set_block_number(1);
+
 		// set leader
-		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
 
 		// check if leader was correctly set in group 1
 		assert_eq!(ArchipelModule::get_leader(1), 10);
 
 		// give_up_leadership 
 		assert_ok!(ArchipelModule::give_up_leadership(Origin::signed(10), 1));
-		
-		// construct event that should be emitted in the method call directly above
-		//let expected_event = Event::generic_event(Event::GiveUpLeader(10,1));
 
-		// iterate through array of `EventRecord`s
-		//assert!(System::events().iter().any(|a| a.event == expected_event));
+		// check that event was emitted in the method call directly above
+		assert!(has_event(crate::Event::GiveUpLeader(10, 1, 1)));
+	})
+}
+
+#[test]
+fn non_member_set_leader_should_fail() {
+	new_test_ext().execute_with(|| {
+		// account 40 is not a federation member
+		assert_noop!(
+			ArchipelModule::set_leader(Origin::signed(40), 0, 1, 0, None),
+			Error::<Test>::NotMember
+		);
+	})
+}
+
+#[test]
+fn non_group_member_set_leader_should_fail() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 2, NodeStatus::Active));
+
+		// member 10 only serves group 2
+		assert_noop!(
+			ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None),
+			Error::<Test>::NotGroupMember
+		);
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 2, 0, None));
+		assert_eq!(ArchipelModule::get_leader(2), 10);
+	})
+}
+
+#[test]
+fn non_member_give_up_leadership_should_fail() {
+	new_test_ext().execute_with(|| {
+		// set leader
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
+
+		// account 40 is not a federation member
+		assert_noop!(
+			ArchipelModule::give_up_leadership(Origin::signed(40), 1),
+			Error::<Test>::NotMember
+		);
+	})
+}
+
+#[test]
+fn non_member_add_heartbeat_should_fail() {
+	new_test_ext().execute_with(|| {
+		// account 40 is not a federation member
+		assert_noop!(
+			ArchipelModule::add_heartbeat(Origin::signed(40), 1, NodeStatus::Passive),
+			Error::<Test>::NotMember
+		);
+
+		// checking that account was not added to structures
+		assert_eq!(ArchipelModule::get_accounts_count().unwrap_or(0), 0);
+	})
+}
+
+#[test]
+fn add_member_should_work() {
+	new_test_ext().execute_with(|| {
+		assert!(!ArchipelModule::is_member(40));
+
+		// add member
+		assert_ok!(ArchipelModule::add_member(Origin::root(), 40));
+		assert!(ArchipelModule::is_member(40));
+
+		// new member can send heartbeats
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(40), 1, NodeStatus::Passive));
+	})
+}
+
+#[test]
+fn add_member_without_admin_origin_should_fail() {
+	new_test_ext().execute_with(|| {
+		// members can't add other members
+		assert_noop!(
+			ArchipelModule::add_member(Origin::signed(10), 40),
+			DispatchError::BadOrigin
+		);
+	})
+}
+
+#[test]
+fn add_existing_member_should_fail() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			ArchipelModule::add_member(Origin::root(), 10),
+			Error::<Test>::AlreadyMember
+		);
+	})
+}
+
+#[test]
+fn remove_member_should_work() {
+	new_test_ext().execute_with(|| {
+		// add heartbeat while being member
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(20), 1, NodeStatus::Passive));
+
+		// remove member
+		assert_ok!(ArchipelModule::remove_member(Origin::root(), 20, 10, 10));
+		assert!(!ArchipelModule::is_member(20));
+
+		// removed member node left its groups
+		assert!(!ArchipelModule::is_in_group(20, 1));
+		assert!(ArchipelModule::get_group_member(1, &20).is_none());
+		assert_eq!(ArchipelModule::get_group_members_count(1), 0);
+		assert_eq!(ArchipelModule::get_accounts_count(), Some(0));
+
+		// removed member can't send heartbeats anymore
+		assert_noop!(
+			ArchipelModule::add_heartbeat(Origin::signed(20), 1, NodeStatus::Passive),
+			Error::<Test>::NotMember
+		);
+	})
+}
+
+#[test]
+fn remove_member_without_admin_origin_should_fail() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			ArchipelModule::remove_member(Origin::signed(10), 20, 10, 10),
+			DispatchError::BadOrigin
+		);
+	})
+}
+
+#[test]
+fn remove_non_member_should_fail() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			ArchipelModule::remove_member(Origin::root(), 40, 10, 10),
+			Error::<Test>::NotMember
+		);
+	})
+}
+
+#[test]
+fn remove_leader_member_should_release_leadership() {
+	new_test_ext().execute_with(|| {
+		// set leader
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
+		assert_eq!(ArchipelModule::get_leaded_group(1), true);
+
+		// remove leader from federation
+		assert_ok!(ArchipelModule::remove_member(Origin::root(), 10, 10, 10));
+
+		// group is not leaded anymore
+		assert_eq!(ArchipelModule::get_leaded_group(1), false);
+
+		// other member can take leadership without old leader report
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(20), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(20), 0, 1, 1, None));
+		assert_eq!(ArchipelModule::get_leader(1), 20);
+	})
+}
+
+#[test]
+fn membership_churn_should_work() {
+	new_test_ext().execute_with(|| {
+		// remove and add back the same member
+		assert_ok!(ArchipelModule::remove_member(Origin::root(), 30, 10, 10));
+		assert_noop!(
+			ArchipelModule::set_leader(Origin::signed(30), 0, 1, 0, None),
+			Error::<Test>::NotMember
+		);
+		assert_ok!(ArchipelModule::add_member(Origin::root(), 30));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(30), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(30), 0, 1, 0, None));
+
+		// add a new member which takes leadership from the previous dead one
+		assert_ok!(ArchipelModule::add_member(Origin::root(), 40));
+		System::set_block_number(AliveTime::get() + 1);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(40), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(40), 30, 1, 1, None));
+		assert_eq!(ArchipelModule::get_leader(1), 40);
+	})
+}
+
+#[test]
+fn set_leader_should_start_lease() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+
+		// set leader
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
+
+		// lease ends LeaseDuration blocks later
+		assert_eq!(ArchipelModule::get_leader_lease(1), 1 + LeaseDuration::get());
+	})
+}
+
+#[test]
+fn leader_heartbeat_should_renew_lease() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
+
+		// leader heartbeat renews lease
+		System::set_block_number(5);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_eq!(ArchipelModule::get_leader_lease(1), 5 + LeaseDuration::get());
+
+		// other nodes heartbeats and leader heartbeats in other groups don't renew lease
+		System::set_block_number(6);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(20), 1, NodeStatus::Passive));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 2, NodeStatus::Active));
+		assert_eq!(ArchipelModule::get_leader_lease(1), 5 + LeaseDuration::get());
+	})
+}
+
+#[test]
+fn expired_lease_should_release_leadership() {
+	new_test_ext().execute_with(|| {
+		run_to_block(1);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
+
+		// lease is still valid one block before its end
+		run_to_block(LeaseDuration::get());
+		assert_eq!(ArchipelModule::get_leaded_group(1), true);
+		assert_eq!(ArchipelModule::get_leader(1), 10);
+
+		// lease expired
+		run_to_block(1 + LeaseDuration::get());
+		assert_eq!(ArchipelModule::get_leaded_group(1), false);
+		assert!(!crate::Leaders::<Test>::contains_key(1));
+		assert!(!crate::LeaderLease::<Test>::contains_key(1));
+		assert!(has_event(crate::Event::LeaderExpired(10, 1)));
+
+		// anyone can take leadership again
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(20), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(20), 0, 1, 1, None));
+	})
+}
+
+#[test]
+fn renewed_lease_should_not_expire() {
+	new_test_ext().execute_with(|| {
+		run_to_block(1);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
+
+		// leader keeps sending heartbeats
+		for block in (10..=100).step_by(10) {
+			run_to_block(block);
+			assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		}
+
+		assert_eq!(ArchipelModule::get_leaded_group(1), true);
+		assert_eq!(ArchipelModule::get_leader(1), 10);
+		assert!(!has_event(crate::Event::LeaderExpired(10, 1)));
+	})
+}
+
+#[test]
+fn leases_should_be_indexed_by_block() {
+	new_test_ext().execute_with(|| {
+		run_to_block(1);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
+		let lease_end = 1 + LeaseDuration::get();
+		assert_eq!(ArchipelModule::get_lease_expiries(lease_end), vec![1]);
+		assert_eq!(ArchipelModule::get_stale_checks(1 + AliveTime::get() + 1), vec![1]);
+
+		// renewed lease and liveness are indexed at their new blocks
+		run_to_block(5);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_eq!(ArchipelModule::get_lease_expiries(5 + LeaseDuration::get()), vec![1]);
+		assert_eq!(ArchipelModule::get_stale_checks(5 + AliveTime::get() + 1), vec![1]);
+
+		// previous entries are skipped and dropped from the indexes
+		run_to_block(1 + AliveTime::get() + 1);
+		assert!(!has_event(crate::Event::LeaderStale(10, 1, 1)));
+		assert!(ArchipelModule::get_stale_checks(1 + AliveTime::get() + 1).is_empty());
+		run_to_block(5 + AliveTime::get() + 1);
+		assert!(has_event(crate::Event::LeaderStale(10, 1, 5)));
+		run_to_block(lease_end);
+		assert!(ArchipelModule::get_lease_expiries(lease_end).is_empty());
+		assert_eq!(ArchipelModule::get_leader(1), 10);
+
+		run_to_block(5 + LeaseDuration::get());
+		assert!(has_event(crate::Event::LeaderExpired(10, 1)));
+		assert!(ArchipelModule::get_lease_expiries(5 + LeaseDuration::get()).is_empty());
+	})
+}
+
+#[test]
+fn stale_leader_event_should_work() {
+	new_test_ext().execute_with(|| {
+		run_to_block(1);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
+
+		// leader is still alive
+		run_to_block(1 + AliveTime::get());
+		assert!(!has_event(crate::Event::LeaderStale(10, 1, 1)));
+
+		// leader is not alive anymore but still holds its lease
+		run_to_block(1 + AliveTime::get() + 1);
+		assert!(has_event(crate::Event::LeaderStale(10, 1, 1)));
+		assert_eq!(ArchipelModule::get_leader(1), 10);
+	})
+}
+
+#[test]
+fn give_up_leadership_should_remove_lease() {
+	new_test_ext().execute_with(|| {
+		run_to_block(1);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
+		assert_ok!(ArchipelModule::give_up_leadership(Origin::signed(10), 1));
+
+		assert!(!crate::LeaderLease::<Test>::contains_key(1));
+
+		// no lease expiry is reported for a released leadership
+		run_to_block(1 + LeaseDuration::get());
+		assert!(!has_event(crate::Event::LeaderExpired(10, 1)));
+	})
+}
+
+#[test]
+fn set_leader_should_increment_epoch() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_eq!(ArchipelModule::get_leadership_epoch(1), 0);
+
+		// first leader of the group starts epoch 1
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
+		assert_eq!(ArchipelModule::get_leadership_epoch(1), 1);
+
+		// leader takeover starts epoch 2
+		System::set_block_number(AliveTime::get() + 2);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(20), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(20), 10, 1, 1, None));
+		assert_eq!(ArchipelModule::get_leadership_epoch(1), 2);
+		assert!(has_event(crate::Event::NewLeader(20, 1, 2)));
+
+		// other groups epochs are not altered
+		assert_eq!(ArchipelModule::get_leadership_epoch(2), 0);
+	})
+}
+
+#[test]
+fn stale_epoch_takeover_should_fail() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
+
+		// 20 takes leadership from dead leader 10
+		System::set_block_number(AliveTime::get() + 2);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(20), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(20), 10, 1, 1, None));
+
+		// 30 acts on an outdated view of the leadership
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(30), 1, NodeStatus::Active));
+		assert_noop!(
+			ArchipelModule::set_leader(Origin::signed(30), 10, 1, 1, None),
+			Error::<Test>::StaleEpoch
+		);
+
+		// first leadership request sent twice can't be replayed
+		assert_noop!(
+			ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None),
+			Error::<Test>::StaleEpoch
+		);
+		assert_eq!(ArchipelModule::get_leader(1), 20);
+	})
+}
+
+#[test]
+fn expired_leadership_request_should_fail() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(6);
+
+		// request was valid until block 5
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_noop!(
+			ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, Some(5)),
+			Error::<Test>::RequestExpired
+		);
+
+		// request is valid until block 6 included
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, Some(6)));
+		assert_eq!(ArchipelModule::get_leader(1), 10);
+	})
+}
+
+#[test]
+fn give_up_leadership_should_keep_epoch() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
+		assert_ok!(ArchipelModule::give_up_leadership(Origin::signed(10), 1));
+		assert_eq!(ArchipelModule::get_leadership_epoch(1), 1);
+
+		// next leader must report the epoch of the given up leadership
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(20), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(20), 0, 1, 1, None));
+		assert_eq!(ArchipelModule::get_leadership_epoch(1), 2);
+	})
+}
+
+#[test]
+fn create_group_should_work() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(ArchipelModule::create_group(Origin::root(), 1, b"kusama".to_vec(), ServiceType::Kusama, 10, 5, 8));
+
+		let info = ArchipelModule::get_group_info(1).unwrap();
+		assert_eq!(info.name.to_vec(), b"kusama".to_vec());
+		assert_eq!(info.service, ServiceType::Kusama);
+		assert_eq!(info.admin, 10);
+		assert_eq!(ArchipelModule::group_alive_time(1), 5);
+		assert_eq!(ArchipelModule::group_lease_duration(1), 8);
+		assert_eq!(ArchipelModule::registered_groups(None, 10).len(), 1);
+		assert!(has_event(crate::Event::GroupCreated(1, 10)));
+
+		// group can't be registered twice
+		assert_noop!(
+			ArchipelModule::create_group(Origin::root(), 1, b"other".to_vec(), ServiceType::Polkadot, 10, 5, 8),
+			Error::<Test>::GroupAlreadyExists
+		);
+
+		// members can't register a group
+		assert_noop!(
+			ArchipelModule::create_group(Origin::signed(10), 2, b"other".to_vec(), ServiceType::Polkadot, 10, 5, 8),
+			DispatchError::BadOrigin
+		);
+
+		// group admin must be a member
+		assert_noop!(
+			ArchipelModule::create_group(Origin::root(), 2, b"other".to_vec(), ServiceType::Polkadot, 40, 5, 8),
+			Error::<Test>::NotMember
+		);
+	})
+}
+
+#[test]
+fn create_group_should_validate_parameters() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			ArchipelModule::create_group(Origin::root(), 1, vec![0u8; 17], ServiceType::Polkadot, 10, 5, 8),
+			Error::<Test>::GroupNameTooLong
+		);
+		assert_noop!(
+			ArchipelModule::create_group(Origin::root(), 1, b"polkadot".to_vec(), ServiceType::Polkadot, 10, 0, 8),
+			Error::<Test>::InvalidGroupParameters
+		);
+		assert_noop!(
+			ArchipelModule::create_group(Origin::root(), 1, b"polkadot".to_vec(), ServiceType::Polkadot, 10, 5, 0),
+			Error::<Test>::InvalidGroupParameters
+		);
+
+		// unregistered groups use runtime defaults
+		assert_eq!(ArchipelModule::group_alive_time(1), AliveTime::get());
+		assert_eq!(ArchipelModule::group_lease_duration(1), LeaseDuration::get());
 	})
 }
 
+#[test]
+fn update_group_should_work() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(ArchipelModule::create_group(Origin::root(), 1, b"kusama".to_vec(), ServiceType::Kusama, 10, 5, 8));
+
+		assert_noop!(
+			ArchipelModule::update_group(Origin::signed(20), 1, b"kusama".to_vec(), ServiceType::Kusama, 10, 6, 9),
+			Error::<Test>::NotGroupAdmin
+		);
+		assert_noop!(
+			ArchipelModule::update_group(Origin::root(), 2, b"kusama".to_vec(), ServiceType::Kusama, 10, 6, 9),
+			Error::<Test>::UnknownGroup
+		);
+
+		assert_ok!(ArchipelModule::update_group(Origin::root(), 1, b"ksm".to_vec(), ServiceType::Kusama, 10, 6, 9));
+		assert_eq!(ArchipelModule::get_group_info(1).unwrap().name.to_vec(), b"ksm".to_vec());
+		assert_eq!(ArchipelModule::group_alive_time(1), 6);
+		assert_eq!(ArchipelModule::group_lease_duration(1), 9);
+		assert!(has_event(crate::Event::GroupUpdated(1)));
+	})
+}
+
+#[test]
+fn group_admin_should_update_group() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(ArchipelModule::create_group(Origin::root(), 1, b"kusama".to_vec(), ServiceType::Kusama, 10, 5, 8));
+
+		// group admin hands the group over to another member
+		assert_ok!(ArchipelModule::update_group(Origin::signed(10), 1, b"ksm".to_vec(), ServiceType::Kusama, 20, 6, 9));
+		let info = ArchipelModule::get_group_info(1).unwrap();
+		assert_eq!((info.admin, info.alive_time), (20, 6));
+		assert_noop!(
+			ArchipelModule::update_group(Origin::signed(20), 1, b"ksm".to_vec(), ServiceType::Kusama, 40, 6, 9),
+			Error::<Test>::NotMember
+		);
+
+		// previous admin lost its rights
+		assert_noop!(
+			ArchipelModule::update_group(Origin::signed(10), 1, b"kusama".to_vec(), ServiceType::Kusama, 10, 5, 8),
+			Error::<Test>::NotGroupAdmin
+		);
+		assert_ok!(ArchipelModule::update_group(Origin::signed(20), 1, b"kusama".to_vec(), ServiceType::Kusama, 20, 5, 8));
+		assert_eq!(ArchipelModule::group_alive_time(1), 5);
+	})
+}
+
+#[test]
+fn members_should_not_reconfigure_occupied_group() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(20), 1, NodeStatus::Passive));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
+
+		// shrinking alive time would let a member take leadership of a live leader
+		assert_noop!(
+			ArchipelModule::create_group(Origin::signed(20), 1, b"kusama".to_vec(), ServiceType::Kusama, 20, 1, 8),
+			DispatchError::BadOrigin
+		);
+
+		assert_ok!(ArchipelModule::create_group(Origin::root(), 1, b"kusama".to_vec(), ServiceType::Kusama, 30, 5, 8));
+		assert_noop!(
+			ArchipelModule::update_group(Origin::signed(20), 1, b"kusama".to_vec(), ServiceType::Kusama, 20, 1, 8),
+			Error::<Test>::NotGroupAdmin
+		);
+		assert_noop!(ArchipelModule::remove_group(Origin::signed(20), 1), Error::<Test>::NotGroupAdmin);
+
+		System::set_block_number(3);
+		assert_noop!(
+			ArchipelModule::set_leader(Origin::signed(20), 10, 1, 1, None),
+			Error::<Test>::LeaderStillAlive
+		);
+	})
+}
+
+#[test]
+fn remove_group_should_work() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(ArchipelModule::create_group(Origin::root(), 1, b"kusama".to_vec(), ServiceType::Kusama, 10, 5, 8));
+
+		assert_noop!(ArchipelModule::remove_group(Origin::signed(20), 1), Error::<Test>::NotGroupAdmin);
+		assert_noop!(ArchipelModule::remove_group(Origin::none(), 1), DispatchError::BadOrigin);
+		assert_ok!(ArchipelModule::remove_group(Origin::root(), 1));
+		assert!(ArchipelModule::get_group_info(1).is_none());
+		assert!(has_event(crate::Event::GroupRemoved(1)));
+
+		// group falls back to runtime defaults
+		assert_eq!(ArchipelModule::group_alive_time(1), AliveTime::get());
+		assert_noop!(
+			ArchipelModule::remove_group(Origin::root(), 1),
+			Error::<Test>::UnknownGroup
+		);
+	})
+}
+
+#[test]
+fn group_admin_should_remove_group() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(ArchipelModule::create_group(Origin::root(), 1, b"kusama".to_vec(), ServiceType::Kusama, 10, 5, 8));
+
+		assert_ok!(ArchipelModule::remove_group(Origin::signed(10), 1));
+		assert!(ArchipelModule::get_group_info(1).is_none());
+		assert!(has_event(crate::Event::GroupRemoved(1)));
+	})
+}
+
+#[test]
+fn group_parameters_should_drive_leadership() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(ArchipelModule::create_group(Origin::root(), 1, b"kusama".to_vec(), ServiceType::Kusama, 10, 5, 8));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
+		assert_eq!(ArchipelModule::get_leader_lease(1), 9);
+
+		// group alive time is shorter than runtime default
+		System::set_block_number(6);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(20), 1, NodeStatus::Active));
+		assert_noop!(
+			ArchipelModule::set_leader(Origin::signed(20), 10, 1, 1, None),
+			Error::<Test>::LeaderStillAlive
+		);
+		System::set_block_number(7);
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(20), 10, 1, 1, None));
+		assert_eq!(ArchipelModule::get_leader_lease(1), 15);
+
+		// group lease expires with group lease duration
+		run_to_block(15);
+		assert!(has_event(crate::Event::LeaderExpired(20, 1)));
+	})
+}
+
+#[test]
+fn heartbeat_should_update_group_members() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(20), 1, NodeStatus::Passive));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(30), 2, NodeStatus::Passive));
+
+		assert_eq!(ArchipelModule::get_group_members_count(1), 2);
+		assert_eq!(ArchipelModule::get_group_members_count(2), 1);
+		assert_eq!(
+			ArchipelModule::get_group_member(1, 10),
+			Some(MemberState { last_heartbeat: 1, status: NodeStatus::Active })
+		);
+
+		// next heartbeat updates member state
+		System::set_block_number(5);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Maintenance));
+		assert_eq!(
+			ArchipelModule::get_group_member(1, 10),
+			Some(MemberState { last_heartbeat: 5, status: NodeStatus::Maintenance })
+		);
+		assert_eq!(ArchipelModule::get_group_members_count(1), 2);
+	})
+}
+
+#[test]
+fn node_should_belong_to_several_groups() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+
+		System::set_block_number(2);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 2, NodeStatus::Passive));
+
+		// heartbeat in group 2 keeps membership of group 1
+		assert_eq!(
+			ArchipelModule::get_group_member(1, 10),
+			Some(MemberState { last_heartbeat: 1, status: NodeStatus::Active })
+		);
+		assert_eq!(
+			ArchipelModule::get_group_member(2, 10),
+			Some(MemberState { last_heartbeat: 2, status: NodeStatus::Passive })
+		);
+		assert_eq!(ArchipelModule::get_group_members_count(1), 1);
+		assert_eq!(ArchipelModule::get_group_members_count(2), 1);
+
+		let mut groups = ArchipelModule::node_groups(&10, None, 10);
+		groups.sort();
+		assert_eq!(groups, vec![1, 2]);
+		assert_eq!(ArchipelModule::get_heartbeat(10), 2);
+	})
+}
+
+#[test]
+fn add_heartbeats_should_work() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(3);
+		assert_ok!(ArchipelModule::add_heartbeats(
+			Origin::signed(10),
+			vec![(1, NodeStatus::Active), (2, NodeStatus::Passive), (3, NodeStatus::Maintenance)]
+		));
+
+		assert_eq!(ArchipelModule::get_node_status(&10, 1), NodeStatus::Active);
+		assert_eq!(ArchipelModule::get_node_status(&10, 2), NodeStatus::Passive);
+		assert_eq!(ArchipelModule::get_node_status(&10, 3), NodeStatus::Maintenance);
+		assert_eq!(ArchipelModule::get_group_heartbeat(&10, 2), 3);
+		assert_eq!(ArchipelModule::get_heartbeat(10), 3);
+		assert_eq!(ArchipelModule::get_accounts_count().unwrap_or(0), 1);
+		assert!(has_event(crate::Event::NewHeartbeat(10, 3, NodeStatus::Maintenance, 3)));
+
+		// non member can't send heartbeats
+		assert_noop!(
+			ArchipelModule::add_heartbeats(Origin::signed(40), vec![(1, NodeStatus::Active)]),
+			Error::<Test>::NotMember
+		);
+	})
+}
+
+#[test]
+fn leader_liveness_should_be_checked_per_group() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
+
+		// heartbeats for another group don't keep group 1 leadership alive
+		System::set_block_number(AliveTime::get() + 1);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 2, NodeStatus::Passive));
+		System::set_block_number(AliveTime::get() + 2);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(20), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(20), 10, 1, 1, None));
+		assert_eq!(ArchipelModule::get_leader(1), 20);
+	})
+}
+
+#[test]
+fn group_members_should_be_paginated() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(20), 1, NodeStatus::Passive));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(30), 1, NodeStatus::Passive));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 2, NodeStatus::Passive));
+
+		let first_page = ArchipelModule::group_members(1, None, 2);
+		assert_eq!(first_page.len(), 2);
+		let second_page = ArchipelModule::group_members(1, Some(first_page[1].account), 2);
+		assert_eq!(second_page.len(), 1);
+
+		let mut members: Vec<u64> = first_page.iter().chain(second_page.iter()).map(|state| state.account).collect();
+		members.sort();
+		assert_eq!(members, vec![10, 20, 30]);
+		assert!(first_page.iter().chain(second_page.iter()).all(|state| state.alive));
+		assert_eq!(ArchipelModule::group_members(1, Some(second_page[0].account), 2), vec![]);
+
+		// members of other groups are not listed
+		assert_eq!(ArchipelModule::group_members(2, None, 5).len(), 1);
+
+		// removing a member doesn't shift the following page
+		let cursor = first_page[0].account;
+		assert_ok!(ArchipelModule::force_remove(Origin::root(), cursor, 10, 10));
+		let page = ArchipelModule::group_members(1, Some(cursor), 5);
+		assert_eq!(page.len(), 2);
+		assert_eq!(page[0].account, first_page[1].account);
+	})
+}
+
+#[test]
+fn node_groups_should_be_paginated() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(ArchipelModule::add_heartbeats(
+			Origin::signed(10),
+			vec![(1, NodeStatus::Active), (2, NodeStatus::Passive), (3, NodeStatus::Passive)]
+		));
+
+		let first_page = ArchipelModule::node_groups(&10, None, 2);
+		assert_eq!(first_page.len(), 2);
+		let second_page = ArchipelModule::node_groups(&10, Some(first_page[1]), 2);
+		assert_eq!(second_page.len(), 1);
+
+		let mut groups: Vec<u32> = first_page.into_iter().chain(second_page.into_iter()).collect();
+		groups.sort();
+		assert_eq!(groups, vec![1, 2, 3]);
+		assert!(ArchipelModule::node_groups(&20, None, 2).is_empty());
+	})
+}
+
+#[test]
+fn registered_groups_should_be_paginated() {
+	new_test_ext().execute_with(|| {
+		for group_id in 1..4 {
+			assert_ok!(ArchipelModule::create_group(Origin::root(), group_id, b"kusama".to_vec(), ServiceType::Kusama, 10, 5, 8));
+		}
+
+		let first_page = ArchipelModule::registered_groups(None, 2);
+		assert_eq!(first_page.len(), 2);
+		assert_eq!(first_page[0].1.name, b"kusama".to_vec());
+		assert_eq!(first_page[0].1.alive_time, 5);
+		let second_page = ArchipelModule::registered_groups(Some(first_page[1].0), 2);
+		assert_eq!(second_page.len(), 1);
+
+		let mut groups: Vec<u32> = first_page.iter().chain(second_page.iter()).map(|(group_id, _)| *group_id).collect();
+		groups.sort();
+		assert_eq!(groups, vec![1, 2, 3]);
+	})
+}
+
+#[test]
+fn deregister_should_compact_accounts() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(20), 1, NodeStatus::Passive));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(30), 2, NodeStatus::Passive));
+
+		assert_ok!(ArchipelModule::deregister(Origin::signed(10), 10, 10));
+
+		// last account takes the removed account slot
+		assert_eq!(ArchipelModule::get_accounts_count(), Some(2));
+		assert_eq!(ArchipelModule::get_account(0), 30);
+		assert_eq!(ArchipelModule::get_accounts_index(30), 0);
+		assert_eq!(ArchipelModule::get_account(1), 20);
+		assert!(!crate::Accounts::<Test>::contains_key(2));
+		assert!(!crate::AccountsIndex::<Test>::contains_key(10));
+
+		// heartbeats and group membership are removed
+		assert!(!crate::Heartbeats::<Test>::contains_key(10));
+		assert!(!ArchipelModule::is_in_group(10, 1));
+		assert_eq!(ArchipelModule::get_group_member(1, 10), None);
+		assert_eq!(ArchipelModule::get_group_members_count(1), 1);
+		assert!(has_event(crate::Event::NodeRemoved(10)));
+
+		// node can't be removed twice
+		assert_noop!(
+			ArchipelModule::deregister(Origin::signed(10), 10, 10),
+			Error::<Test>::UnknownNode
+		);
+
+		// removing last account doesn't move anything
+		assert_ok!(ArchipelModule::deregister(Origin::signed(20), 10, 10));
+		assert_eq!(ArchipelModule::get_accounts_count(), Some(1));
+		assert_eq!(ArchipelModule::get_account(0), 30);
+	})
+}
+
+#[test]
+fn deregister_should_release_leadership() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
+
+		assert_ok!(ArchipelModule::deregister(Origin::signed(10), 10, 10));
+		assert_eq!(ArchipelModule::get_leaded_group(1), false);
+		assert!(!crate::Leaders::<Test>::contains_key(1));
+		assert!(!crate::LeaderLease::<Test>::contains_key(1));
+		assert!(has_event(crate::Event::GiveUpLeader(10, 1, 1)));
+	})
+}
+
+#[test]
+fn force_remove_should_work() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+
+		assert_noop!(
+			ArchipelModule::force_remove(Origin::signed(20), 10, 10, 10),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			ArchipelModule::force_remove(Origin::root(), 20, 10, 10),
+			Error::<Test>::UnknownNode
+		);
+
+		assert_ok!(ArchipelModule::force_remove(Origin::root(), 10, 10, 10));
+		assert_eq!(ArchipelModule::get_accounts_count(), Some(0));
+		assert!(has_event(crate::Event::NodeRemoved(10)));
+	})
+}
+
+#[test]
+fn node_removal_should_check_witnesses() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 2, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(20), 3, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(20), 0, 3, 0, None));
+
+		// node is in 2 groups and 2 groups are leaded
+		assert_noop!(
+			ArchipelModule::deregister(Origin::signed(10), 1, 2),
+			Error::<Test>::InvalidWitness
+		);
+		assert_noop!(
+			ArchipelModule::force_remove(Origin::root(), 10, 2, 1),
+			Error::<Test>::InvalidWitness
+		);
+		assert_noop!(
+			ArchipelModule::remove_member(Origin::root(), 20, 10, 1),
+			Error::<Test>::InvalidWitness
+		);
+		assert_noop!(
+			ArchipelModule::remove_member(Origin::root(), 20, 0, 10),
+			Error::<Test>::InvalidWitness
+		);
+
+		// actual counts are charged whatever the witnesses
+		let info = ArchipelModule::deregister(Origin::signed(10), 10, 10).unwrap();
+		assert_eq!(info.actual_weight, Some(<() as WeightInfo>::deregister(2, 2)));
+		let info = ArchipelModule::remove_member(Origin::root(), 20, 10, 10).unwrap();
+		assert_eq!(info.actual_weight, Some(<() as WeightInfo>::remove_member(1, 1)));
+	})
+}
+
+#[test]
+fn on_idle_should_prune_stale_nodes() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(20), 1, NodeStatus::Passive));
+
+		System::set_block_number(50);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(30), 1, NodeStatus::Passive));
+
+		// nobody is stale yet
+		ArchipelModule::on_idle(PruneAfter::get() + 1, u64::MAX);
+		assert_eq!(ArchipelModule::get_accounts_count(), Some(3));
+
+		// 10 and 20 stopped sending heartbeats
+		let now = PruneAfter::get() + 2;
+		System::set_block_number(now);
+		ArchipelModule::on_idle(now, u64::MAX);
+		assert_eq!(ArchipelModule::get_accounts_count(), Some(1));
+		assert_eq!(ArchipelModule::get_account(0), 30);
+		assert!(has_event(crate::Event::NodeRemoved(10)));
+		assert!(has_event(crate::Event::NodeRemoved(20)));
+		assert_eq!(ArchipelModule::get_group_members_count(1), 1);
+	})
+}
+
+#[test]
+fn on_idle_should_keep_nodes_without_heartbeat() {
+	new_test_ext_with_groups(vec![(10, 1), (20, 1)], vec![]).execute_with(|| {
+		// genesis group members didn't send any heartbeat yet
+		let now = PruneAfter::get() + 2;
+		System::set_block_number(now);
+		ArchipelModule::on_idle(now, u64::MAX);
+		assert_eq!(ArchipelModule::get_accounts_count(), Some(2));
+		assert_eq!(ArchipelModule::get_group_members_count(1), 2);
+
+		// they are pruned once they stop sending heartbeats
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		let now = 2 * PruneAfter::get() + 3;
+		System::set_block_number(now);
+		ArchipelModule::on_idle(now, u64::MAX);
+		assert_eq!(ArchipelModule::get_accounts_count(), Some(1));
+		assert_eq!(ArchipelModule::get_account(0), 20);
+		assert!(has_event(crate::Event::NodeRemoved(10)));
+	})
+}
+
+#[test]
+fn on_idle_should_respect_remaining_weight() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(20), 1, NodeStatus::Passive));
+
+		// no weight left so nothing is pruned
+		let now = PruneAfter::get() + 2;
+		assert_eq!(ArchipelModule::on_idle(now, 0), 0);
+		assert_eq!(ArchipelModule::get_accounts_count(), Some(2));
+	})
+}
+
+#[test]
+fn on_idle_should_account_for_node_groups() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(ArchipelModule::add_heartbeats(
+			Origin::signed(10),
+			vec![(1, NodeStatus::Active), (2, NodeStatus::Passive), (3, NodeStatus::Passive)]
+		));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(20), 1, NodeStatus::Passive));
+
+		// weight is enough to remove a node in a single group but not in three groups
+		let now = PruneAfter::get() + 2;
+		System::set_block_number(now);
+		assert!(ArchipelModule::on_idle(now, 25_000) <= 25_000);
+		assert_eq!(ArchipelModule::get_accounts_count(), Some(2));
+		assert_eq!(ArchipelModule::get_prune_cursor(), 0);
+
+		assert_eq!(ArchipelModule::on_idle(now, 27_700), 27_700);
+		assert_eq!(ArchipelModule::get_accounts_count(), Some(1));
+		assert_eq!(ArchipelModule::get_account(0), 20);
+		assert!(ArchipelModule::node_groups(&10, None, 10).is_empty());
+	})
+}
+
+#[test]
+fn genesis_should_set_groups_and_leaders() {
+	new_test_ext_with_groups(vec![(10, 1), (20, 1), (30, 2)], vec![(1, 10)]).execute_with(|| {
+		assert_eq!(ArchipelModule::get_accounts_count(), Some(3));
+		assert!(ArchipelModule::is_in_group(10, 1));
+		assert!(ArchipelModule::is_in_group(30, 2));
+		assert_eq!(ArchipelModule::get_group_members_count(1), 2);
+		assert_eq!(ArchipelModule::get_node_status(&20, 1), NodeStatus::Unknown);
+
+		// group 1 is leaded from genesis
+		assert_eq!(ArchipelModule::get_leader(1), 10);
+		assert_eq!(ArchipelModule::get_leaded_group(1), true);
+		assert_eq!(ArchipelModule::get_leadership_epoch(1), 1);
+		assert_eq!(ArchipelModule::get_leader_lease(1), LeaseDuration::get());
+		assert_eq!(ArchipelModule::get_leaded_group(2), false);
+
+		// genesis leader must send heartbeats to keep leadership
+		System::set_block_number(AliveTime::get() + 1);
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(20), 10, 1, 1, None));
+		assert_eq!(ArchipelModule::get_leader(1), 20);
+	})
+}
+
+#[test]
+#[should_panic(expected = "Group members must be federation members")]
+fn genesis_group_of_non_member_should_fail() {
+	new_test_ext_with_groups(vec![(40, 1)], vec![]);
+}
+
+#[test]
+#[should_panic(expected = "Group leaders must belong to their group")]
+fn genesis_leader_outside_group_should_fail() {
+	new_test_ext_with_groups(vec![(10, 1)], vec![(2, 10)]);
+}
+
+// Writing a value with the first release layout
+fn put_v1_value<V: Encode>(item: &[u8], account: u64, value: V) {
+	let pallet = ArchipelModule::name().as_bytes();
+	put_storage_value(pallet, item, &Twox64Concat::hash(&account.encode()), value);
+}
+
+fn populate_v1_state() {
+	crate::StorageVersion::<Test>::put(Releases::V1_0_0);
+
+	// 10 and 40 served groups before the membership allowlist
+	crate::Accounts::<Test>::insert(0, 10);
+	crate::Accounts::<Test>::insert(1, 40);
+	crate::AccountsCount::<Test>::put(2);
+	put_v1_value(b"AccountsIndex", 10, 0u32);
+	put_v1_value(b"AccountsIndex", 40, 1u32);
+	put_v1_value(b"Heartbeats", 10, 5u64);
+	put_v1_value(b"Heartbeats", 40, 7u64);
+	put_v1_value(b"NodesStatus", 10, 1u32);
+	put_v1_value(b"NodesStatus", 40, 2u32);
+	put_v1_value(b"Groups", 10, 1u32);
+	put_v1_value(b"Groups", 40, 2u32);
+
+	// 10 leads group 1 without lease
+	crate::Leaders::<Test>::insert(1, 10);
+	crate::LeadedGroup::<Test>::insert(1, true);
+}
+
+#[test]
+fn genesis_should_set_latest_storage_version() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(ArchipelModule::storage_version(), Releases::V2_0_0);
+	})
+}
+
+#[test]
+fn migration_to_v2_should_work() {
+	new_test_ext().execute_with(|| {
+		populate_v1_state();
+		System::set_block_number(8);
+
+		ArchipelModule::on_runtime_upgrade();
+		assert_eq!(ArchipelModule::storage_version(), Releases::V2_0_0);
+
+		// account keyed maps are readable with new hashers
+		assert_eq!(ArchipelModule::get_heartbeat(10), 5);
+		assert_eq!(ArchipelModule::get_heartbeat(40), 7);
+		assert_eq!(ArchipelModule::get_accounts_index(40), 1);
+
+		// old entries are removed
+		let pallet = ArchipelModule::name().as_bytes();
+		let old_key = Twox64Concat::hash(&10u64.encode());
+		assert_eq!(get_storage_value::<u64>(pallet, b"Heartbeats", &old_key), None);
+		assert_eq!(get_storage_value::<u32>(pallet, b"NodesStatus", &old_key), None);
+
+		// nodes keep their group, last heartbeat and status
+		assert_eq!(
+			ArchipelModule::get_group_member(1, 10),
+			Some(MemberState { last_heartbeat: 5, status: NodeStatus::Active })
+		);
+		assert_eq!(
+			ArchipelModule::get_group_member(2, 40),
+			Some(MemberState { last_heartbeat: 7, status: NodeStatus::Passive })
+		);
+		assert!(ArchipelModule::is_in_group(40, 2));
+		assert_eq!(ArchipelModule::get_group_members_count(2), 1);
+
+		// nodes serving a group and listed accounts become members, leaders get a lease
+		assert!(ArchipelModule::is_member(40));
+		assert!(ArchipelModule::is_member(50));
+		assert!(!ArchipelModule::is_member(60));
+		assert_eq!(ArchipelModule::get_leader_lease(1), 8 + LeaseDuration::get());
+	})
+}
+
+#[test]
+fn migrated_nodes_should_keep_working() {
+	new_test_ext().execute_with(|| {
+		populate_v1_state();
+		System::set_block_number(8);
+		ArchipelModule::on_runtime_upgrade();
+
+		// nodes known before the upgrade keep sending heartbeats and leading their groups
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(40), 2, NodeStatus::Active));
+		assert_eq!(ArchipelModule::get_accounts_count(), Some(2));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(40), 0, 2, 0, None));
+		assert_eq!(ArchipelModule::get_leader(2), 40);
+
+		// unknown accounts still need to be added by the admin
+		assert_noop!(
+			ArchipelModule::add_heartbeat(Origin::signed(60), 2, NodeStatus::Active),
+			Error::<Test>::NotMember
+		);
+	})
+}
+
+#[test]
+fn migration_should_run_once() {
+	new_test_ext().execute_with(|| {
+		populate_v1_state();
+		ArchipelModule::on_runtime_upgrade();
+
+		System::set_block_number(9);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Passive));
+
+		// next upgrade doesn't touch migrated storage
+		ArchipelModule::on_runtime_upgrade();
+		assert_eq!(ArchipelModule::get_heartbeat(10), 9);
+		assert_eq!(ArchipelModule::get_node_status(&10, 1), NodeStatus::Passive);
+	})
+}
+
+#[test]
+fn group_state_should_be_consistent() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
+
+		System::set_block_number(5);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(20), 1, NodeStatus::Passive));
+
+		// 10 is not alive anymore while 20 is
+		System::set_block_number(2 + AliveTime::get());
+		let state = ArchipelModule::group_state(1);
+		assert_eq!(state.block_number, 2 + AliveTime::get());
+		assert_eq!(state.leader, Some(10));
+		assert_eq!(state.epoch, 1);
+		assert_eq!(state.lease_end, Some(1 + LeaseDuration::get()));
+
+		let mut members = state.members.clone();
+		members.sort_by_key(|member| member.account);
+		assert_eq!(members.len(), 2);
+		assert_eq!((members[0].account, members[0].status, members[0].alive), (10, NodeStatus::Active, false));
+		assert_eq!((members[1].account, members[1].last_heartbeat, members[1].alive), (20, 5, true));
+
+		// group without leader
+		let state = ArchipelModule::group_state(2);
+		assert_eq!(state.leader, None);
+		assert_eq!(state.lease_end, None);
+		assert!(state.members.is_empty());
+	})
+}
+
+#[test]
+fn leadership_should_follow_leader_changes() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let leadership = ArchipelModule::leadership(1);
+		assert_eq!((leadership.leader, leadership.epoch, leadership.lease_end), (None, 0, None));
+
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
+		let leadership = ArchipelModule::leadership(1);
+		assert_eq!(leadership.block_number, 1);
+		assert_eq!((leadership.leader, leadership.epoch), (Some(10), 1));
+		assert_eq!(leadership.lease_end, Some(1 + LeaseDuration::get()));
+
+		// leadership given up keeps its epoch
+		assert_ok!(ArchipelModule::give_up_leadership(Origin::signed(10), 1));
+		let leadership = ArchipelModule::leadership(1);
+		assert_eq!((leadership.leader, leadership.epoch), (None, 1));
+	})
+}
+
+#[test]
+fn leadership_keys_should_match_verifier() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
+
+		let keys = ArchipelModule::leadership_keys(1);
+		assert_eq!(keys, archipel_verifier::leadership_keys(ArchipelModule::name().as_bytes(), 1));
+		assert_eq!(sp_io::storage::get(&keys[0]), Some(10u64.encode()));
+		assert_eq!(sp_io::storage::get(&keys[1]), Some(1u64.encode()));
+		assert_eq!(sp_io::storage::get(&keys[2]), Some(true.encode()));
+	})
+}
+
+fn recommendation(account: u64, group_id: u32) -> (RecommendedAction, ActionReason) {
+	let recommendation = ArchipelModule::recommended_action(&account, group_id);
+	(recommendation.action, recommendation.reason)
+}
+
+#[test]
+fn recommended_action_should_require_liveness() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+
+		// non member and silent node stay passive
+		assert_eq!(recommendation(40, 1), (RecommendedAction::StayPassive, ActionReason::NotMember));
+		assert_eq!(recommendation(10, 1), (RecommendedAction::StayPassive, ActionReason::NodeNotAlive));
+
+		// isolated node stays passive
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Passive));
+		assert_eq!(recommendation(10, 1), (RecommendedAction::StayPassive, ActionReason::NoOtherMemberAlive));
+
+		// node in maintenance stays passive
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(20), 1, NodeStatus::Maintenance));
+		assert_eq!(recommendation(20, 1), (RecommendedAction::StayPassive, ActionReason::Maintenance));
+	})
+}
+
+#[test]
+fn recommended_action_should_follow_leadership() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Passive));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(20), 1, NodeStatus::Passive));
+
+		// group without leader
+		assert_eq!(recommendation(10, 1), (RecommendedAction::BecomeLeader, ActionReason::NoLeader));
+
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
+		assert_eq!(recommendation(10, 1), (RecommendedAction::StayActive, ActionReason::IsLeader));
+		assert_eq!(recommendation(20, 1), (RecommendedAction::StayPassive, ActionReason::LeaderAlive));
+		assert_eq!(ArchipelModule::recommended_action(&20, 1).epoch, 1);
+		assert_eq!(ArchipelModule::recommended_action(&20, 1).leader, Some(10));
+
+		// leader stopped sending heartbeats
+		System::set_block_number(2 + AliveTime::get());
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(20), 1, NodeStatus::Passive));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(30), 1, NodeStatus::Passive));
+		assert_eq!(recommendation(20, 1), (RecommendedAction::BecomeLeader, ActionReason::LeaderNotAlive));
+		assert_eq!(recommendation(10, 1), (RecommendedAction::GiveUp, ActionReason::NodeNotAlive));
+
+		// following recommendation is accepted by the pallet
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(20), 10, 1, 1, None));
+		assert_eq!(recommendation(20, 1), (RecommendedAction::StayActive, ActionReason::IsLeader));
+	})
+}
+
+// Group events deposited in current block.
+fn group_events(group_id: u32) -> Vec<GroupEvent<u64, u64>> {
+	System::events()
+		.into_iter()
+		.filter_map(|record| match record.event {
+			Event::pallet_archipel(event) => ArchipelModule::group_event(&event, group_id),
+			_ => None,
+		})
+		.collect()
+}
+
+#[test]
+fn group_events_should_be_filtered_by_group() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(20), 2, NodeStatus::Passive));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 0, 1, 0, None));
+		assert_ok!(ArchipelModule::add_member(Origin::root(), 40));
+
+		let events = group_events(1);
+		assert_eq!(events, vec![
+			GroupEvent::NewHeartbeat { account: 10, status: NodeStatus::Active, block_number: 1 },
+			GroupEvent::NewLeader { account: 10, epoch: 1 },
+		]);
+		assert_eq!(events.iter().filter(|event| event.is_leader_change()).count(), 1);
+
+		assert_ok!(ArchipelModule::give_up_leadership(Origin::signed(10), 1));
+		assert_eq!(group_events(1).last(), Some(&GroupEvent::GiveUpLeader { account: 10, epoch: 1 }));
+		assert_eq!(group_events(2), vec![
+			GroupEvent::NewHeartbeat { account: 20, status: NodeStatus::Passive, block_number: 1 },
+		]);
+		assert!(group_events(3).is_empty());
+	})
+}
+
+#[test]
+fn offchain_worker_should_send_heartbeats() {
+	let (offchain, _) = TestOffchainExt::new();
+	let (pool, pool_state) = TestTransactionPoolExt::new();
+	let mut ext = new_test_ext();
+	ext.register_extension(OffchainDbExt::new(offchain.clone()));
+	ext.register_extension(OffchainWorkerExt::new(offchain));
+	ext.register_extension(TransactionPoolExt::new(pool));
+
+	ext.execute_with(|| {
+		UintAuthorityId::set_all_keys(vec![10]);
+
+		// nothing is sent until group and status are configured
+		ArchipelModule::offchain_worker(HeartbeatInterval::get());
+		assert!(pool_state.read().transactions.is_empty());
+
+		sp_io::offchain::local_storage_set(StorageKind::PERSISTENT, OFFCHAIN_GROUP_KEY, &2u32.encode());
+		sp_io::offchain::local_storage_set(StorageKind::PERSISTENT, OFFCHAIN_STATUS_KEY, &NodeStatus::Passive.encode());
+
+		// heartbeats are only sent every interval
+		ArchipelModule::offchain_worker(HeartbeatInterval::get() + 1);
+		assert!(pool_state.read().transactions.is_empty());
+
+		System::set_block_number(2 * HeartbeatInterval::get());
+		ArchipelModule::offchain_worker(2 * HeartbeatInterval::get());
+		let transaction = pool_state.write().transactions.pop().unwrap();
+		assert!(pool_state.read().transactions.is_empty());
+
+		// heartbeat is unsigned and carries a payload signed by the node key
+		let transaction = Extrinsic::decode(&mut &*transaction).unwrap();
+		assert_eq!(transaction.signature, None);
+		let heartbeat = Heartbeat {
+			public: UintAuthorityId(10),
+			group_id: 2,
+			status: NodeStatus::Passive,
+			block_number: 2 * HeartbeatInterval::get(),
+		};
+		let signature = UintAuthorityId(10).sign(&heartbeat.encode()).unwrap();
+		assert_eq!(transaction.call, Call::ArchipelModule(crate::Call::heartbeat(heartbeat, signature)));
+	})
+}
+
+// Unsigned heartbeat of a node signed with its key.
+fn unsigned_heartbeat(account: u64, group_id: u32, block_number: u64) -> crate::Call<Test> {
+	let heartbeat = Heartbeat { public: UintAuthorityId(account), group_id, status: NodeStatus::Active, block_number };
+	let signature = UintAuthorityId(account).sign(&heartbeat.encode()).unwrap();
+	crate::Call::heartbeat(heartbeat, signature)
+}
+
+fn validate(call: &crate::Call<Test>) -> Result<(), TransactionValidityError> {
+	ArchipelModule::validate_unsigned(TransactionSource::External, call).map(|_| ())
+}
+
+// Registering group 1 and making account join it with a signed heartbeat at block 1.
+fn join_group(account: u64) {
+	System::set_block_number(1);
+	if ArchipelModule::get_group_info(1).is_none() {
+		let (alive_time, lease_duration) = (AliveTime::get(), LeaseDuration::get());
+		assert_ok!(ArchipelModule::create_group(Origin::root(), 1, b"kusama".to_vec(), ServiceType::Kusama, 10, alive_time, lease_duration));
+	}
+	assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(account), 1, NodeStatus::Active));
+}
+
+#[test]
+fn unsigned_heartbeat_should_work() {
+	new_test_ext().execute_with(|| {
+		join_group(10);
+		System::set_block_number(10);
+		let call = unsigned_heartbeat(10, 1, 9);
+		assert_ok!(validate(&call));
+
+		let heartbeat = match call {
+			crate::Call::heartbeat(heartbeat, signature) => {
+				assert_ok!(ArchipelModule::heartbeat(Origin::none(), heartbeat.clone(), signature));
+				heartbeat
+			}
+			_ => unreachable!(),
+		};
+		assert_eq!(ArchipelModule::get_group_heartbeat(&10, 1), 10);
+		assert_eq!(ArchipelModule::get_node_status(&10, 1), NodeStatus::Active);
+		assert!(has_event(crate::Event::NewHeartbeat(10, 1, NodeStatus::Active, 10)));
+
+		// repeated heartbeat is refunded
+		System::set_block_number(11);
+		if let crate::Call::heartbeat(heartbeat, signature) = unsigned_heartbeat(10, 1, 10) {
+			let info = ArchipelModule::heartbeat(Origin::none(), heartbeat, signature).unwrap();
+			assert_eq!(info.actual_weight, Some(<() as WeightInfo>::add_heartbeat() + 2 * TestDbWeight::get().read));
+			assert!(info.actual_weight < Some(<() as WeightInfo>::heartbeat()));
+		}
+
+		// heartbeat can't be replayed
+		assert_eq!(validate(&unsigned_heartbeat(10, 1, 9)), Err(InvalidTransaction::Stale.into()));
+		// signed origin is rejected
+		assert_noop!(
+			ArchipelModule::heartbeat(Origin::signed(10), heartbeat, TestSignature(10, vec![])),
+			DispatchError::BadOrigin,
+		);
+	})
+}
+
+#[test]
+fn unsigned_heartbeat_should_be_validated() {
+	new_test_ext().execute_with(|| {
+		join_group(10);
+		System::set_block_number(20);
+
+		// block window
+		assert_eq!(validate(&unsigned_heartbeat(10, 1, 21)), Err(InvalidTransaction::Future.into()));
+		assert_eq!(validate(&unsigned_heartbeat(10, 1, 20 - AliveTime::get())), Err(InvalidTransaction::Stale.into()));
+		assert_ok!(validate(&unsigned_heartbeat(10, 1, 21 - AliveTime::get())));
+
+		// membership
+		assert_eq!(
+			validate(&unsigned_heartbeat(40, 1, 20)),
+			Err(InvalidTransaction::Custom(INVALID_MEMBER).into()),
+		);
+
+		// group registration and membership
+		assert_eq!(validate(&unsigned_heartbeat(10, 2, 20)), Err(InvalidTransaction::Custom(INVALID_GROUP).into()));
+		assert_eq!(
+			validate(&unsigned_heartbeat(20, 1, 20)),
+			Err(InvalidTransaction::Custom(INVALID_GROUP_MEMBER).into()),
+		);
+		join_group(20);
+		System::set_block_number(20);
+		assert_ok!(validate(&unsigned_heartbeat(20, 1, 20)));
+
+		// signature
+		let heartbeat = Heartbeat { public: UintAuthorityId(10), group_id: 1, status: NodeStatus::Active, block_number: 20 };
+		let signature = UintAuthorityId(20).sign(&heartbeat.encode()).unwrap();
+		assert_eq!(
+			validate(&crate::Call::heartbeat(heartbeat, signature)),
+			Err(InvalidTransaction::BadProof.into()),
+		);
+
+		// other calls are not accepted unsigned
+		assert_eq!(
+			validate(&crate::Call::add_heartbeat(1, NodeStatus::Active)),
+			Err(InvalidTransaction::Call.into()),
+		);
+	})
+}
+
+// Proof of an archipel key for a member at its current keys nonce.
+fn keys_proof(account: u64, key: u64) -> TestSignature {
+	let proof = KeysProof { public: UintAuthorityId(key), account, nonce: ArchipelModule::keys_nonce(account) };
+	UintAuthorityId(key).sign(&proof.encode()).unwrap()
+}
+
+#[test]
+fn set_keys_should_work() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+
+		// only members can set keys
+		assert_noop!(
+			ArchipelModule::set_keys(Origin::signed(40), UintAuthorityId(50), keys_proof(40, 50)),
+			Error::<Test>::NotMember
+		);
+
+		assert_ok!(ArchipelModule::set_keys(Origin::signed(10), UintAuthorityId(50), keys_proof(10, 50)));
+		assert_eq!(ArchipelModule::get_keys(&10), Some(UintAuthorityId(50)));
+		assert_eq!(ArchipelModule::key_account(&UintAuthorityId(50)), 10);
+		assert!(has_event(crate::Event::KeysSet(10)));
+
+		// rotated key replaces previous key
+		assert_ok!(ArchipelModule::set_keys(Origin::signed(10), UintAuthorityId(60), keys_proof(10, 60)));
+		assert_eq!(ArchipelModule::get_keys(&10), Some(UintAuthorityId(60)));
+		assert_eq!(ArchipelModule::get_key_owner(&UintAuthorityId(50)), None);
+		assert_eq!(ArchipelModule::key_account(&UintAuthorityId(50)), 50);
+
+		// keys of other members can't be taken
+		assert_noop!(
+			ArchipelModule::set_keys(Origin::signed(20), UintAuthorityId(60), keys_proof(20, 60)),
+			Error::<Test>::KeyInUse
+		);
+		assert_noop!(
+			ArchipelModule::set_keys(Origin::signed(20), UintAuthorityId(30), keys_proof(20, 30)),
+			Error::<Test>::KeyInUse
+		);
+		assert_ok!(ArchipelModule::set_keys(Origin::signed(20), UintAuthorityId(20), keys_proof(20, 20)));
+
+		assert_ok!(ArchipelModule::purge_keys(Origin::signed(10)));
+		assert_eq!(ArchipelModule::get_keys(&10), None);
+		assert_eq!(ArchipelModule::get_key_owner(&UintAuthorityId(60)), None);
+		assert!(has_event(crate::Event::KeysPurged(10)));
+		assert_noop!(ArchipelModule::purge_keys(Origin::signed(10)), Error::<Test>::NoKeys);
+	})
+}
+
+#[test]
+fn set_keys_should_require_key_proof() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+
+		// key must sign the proof
+		assert_noop!(
+			ArchipelModule::set_keys(Origin::signed(10), UintAuthorityId(50), keys_proof(10, 60)),
+			Error::<Test>::InvalidKeysProof
+		);
+
+		// proof of another member can't be front-run
+		let proof = keys_proof(10, 50);
+		assert_noop!(
+			ArchipelModule::set_keys(Origin::signed(20), UintAuthorityId(50), proof.clone()),
+			Error::<Test>::InvalidKeysProof
+		);
+		assert_ok!(ArchipelModule::set_keys(Origin::signed(10), UintAuthorityId(50), proof.clone()));
+		assert_eq!(ArchipelModule::keys_nonce(10), 1);
+
+		// proof can't be replayed after the key was purged
+		assert_ok!(ArchipelModule::purge_keys(Origin::signed(10)));
+		assert_noop!(
+			ArchipelModule::set_keys(Origin::signed(10), UintAuthorityId(50), proof),
+			Error::<Test>::InvalidKeysProof
+		);
+		assert_ok!(ArchipelModule::set_keys(Origin::signed(10), UintAuthorityId(50), keys_proof(10, 50)));
+	})
+}
+
+#[test]
+fn session_keys_should_rotate_member_keys() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(ArchipelModule::set_keys(Origin::signed(20), UintAuthorityId(60), keys_proof(20, 60)));
+
+		ArchipelModule::rotate_session_keys(vec![
+			(10, UintAuthorityId(50)),
+			// key owned by another member is skipped
+			(30, UintAuthorityId(60)),
+			// validators outside of the federation don't get a key
+			(40, UintAuthorityId(70)),
+		].into_iter());
+		assert_eq!(ArchipelModule::get_keys(&10), Some(UintAuthorityId(50)));
+		assert_eq!(ArchipelModule::key_account(&UintAuthorityId(50)), 10);
+		assert_eq!(ArchipelModule::get_keys(&30), None);
+		assert_eq!(ArchipelModule::key_account(&UintAuthorityId(60)), 20);
+		assert_eq!(ArchipelModule::get_keys(&40), None);
+
+		// rotated session key replaces previous key
+		ArchipelModule::rotate_session_keys(vec![(10, UintAuthorityId(80))].into_iter());
+		assert_eq!(ArchipelModule::get_keys(&10), Some(UintAuthorityId(80)));
+		assert_eq!(ArchipelModule::get_key_owner(&UintAuthorityId(50)), None);
+	})
+}
+
+#[test]
+fn heartbeat_of_rotated_key_should_be_recorded_for_member() {
+	new_test_ext().execute_with(|| {
+		join_group(10);
+		System::set_block_number(10);
+		assert_ok!(ArchipelModule::set_keys(Origin::signed(10), UintAuthorityId(50), keys_proof(10, 50)));
+
+		// key 50 is not a member account but signs for member 10
+		let call = unsigned_heartbeat(50, 1, 10);
+		assert_ok!(validate(&call));
+		if let crate::Call::heartbeat(heartbeat, signature) = call {
+			assert_ok!(ArchipelModule::heartbeat(Origin::none(), heartbeat, signature));
+		}
+		assert_eq!(ArchipelModule::get_group_heartbeat(&10, 1), 10);
+		assert!(!ArchipelModule::is_in_group(&50, 1));
+
+		// removed member releases its key
+		assert_ok!(ArchipelModule::remove_member(Origin::root(), 10, 10, 10));
+		assert_eq!(ArchipelModule::get_key_owner(&UintAuthorityId(50)), None);
+		assert_eq!(
+			validate(&unsigned_heartbeat(50, 1, 10)),
+			Err(InvalidTransaction::Custom(INVALID_MEMBER).into()),
+		);
+	})
+}
+
+#[test]
+fn force_set_leader_should_work() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(20), 1, NodeStatus::Passive));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 10, 1, 0, None));
+
+		assert_noop!(ArchipelModule::force_set_leader(Origin::signed(20), 1, 20), DispatchError::BadOrigin);
+		assert_noop!(ArchipelModule::force_set_leader(Origin::root(), 1, 40), Error::<Test>::NotMember);
+		assert_noop!(ArchipelModule::force_set_leader(Origin::root(), 1, 30), Error::<Test>::NotGroupMember);
+
+		// leader is replaced even if it is still alive
+		assert_ok!(ArchipelModule::force_set_leader(Origin::root(), 1, 20));
+		assert_eq!(ArchipelModule::get_leader(1), 20);
+		assert_eq!(ArchipelModule::get_leadership_epoch(1), 2);
+		assert_eq!(ArchipelModule::get_leader_lease(1), 1 + LeaseDuration::get());
+		assert!(has_event(crate::Event::LeaderForced(20, 1, 2)));
+		assert_eq!(
+			ArchipelModule::group_event(&crate::Event::LeaderForced(20, 1, 2), 1),
+			Some(GroupEvent::NewLeader { account: 20, epoch: 2 }),
+		);
+
+		// previous leader can't reuse its epoch
+		assert_noop!(ArchipelModule::set_leader(Origin::signed(10), 20, 1, 1, None), Error::<Test>::StaleEpoch);
+	})
+}
+
+#[test]
+fn force_give_up_should_work() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_noop!(ArchipelModule::force_give_up(Origin::root(), 1), Error::<Test>::NoLeader);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 10, 1, 0, None));
+
+		assert_noop!(ArchipelModule::force_give_up(Origin::signed(10), 1), DispatchError::BadOrigin);
+		assert_ok!(ArchipelModule::force_give_up(Origin::root(), 1));
+		assert!(!ArchipelModule::get_leaded_group(1));
+		assert!(!crate::LeaderLease::<Test>::contains_key(1));
+		assert!(has_event(crate::Event::GiveUpForced(10, 1, 1)));
+
+		// any member can take leadership back
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(20), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(20), 10, 1, 1, None));
+	})
+}
+
+#[test]
+fn force_set_status_should_work() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(3);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+
+		assert_noop!(
+			ArchipelModule::force_set_status(Origin::signed(10), 10, 1, NodeStatus::Maintenance),
+			DispatchError::BadOrigin,
+		);
+		assert_noop!(
+			ArchipelModule::force_set_status(Origin::root(), 10, 2, NodeStatus::Maintenance),
+			Error::<Test>::UnknownNode,
+		);
+
+		assert_ok!(ArchipelModule::force_set_status(Origin::root(), 10, 1, NodeStatus::Maintenance));
+		assert_eq!(ArchipelModule::get_node_status(&10, 1), NodeStatus::Maintenance);
+		assert_eq!(ArchipelModule::get_group_heartbeat(&10, 1), 3);
+		assert!(has_event(crate::Event::StatusForced(10, 1, NodeStatus::Maintenance)));
+	})
+}
+
+#[test]
+fn force_clear_group_should_work() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(10), 1, NodeStatus::Active));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(20), 1, NodeStatus::Passive));
+		assert_ok!(ArchipelModule::add_heartbeat(Origin::signed(20), 2, NodeStatus::Passive));
+		assert_ok!(ArchipelModule::set_leader(Origin::signed(10), 10, 1, 0, None));
+
+		assert_noop!(ArchipelModule::force_clear_group(Origin::signed(10), 1, 2), DispatchError::BadOrigin);
+		assert_noop!(ArchipelModule::force_clear_group(Origin::root(), 1, 1), Error::<Test>::InvalidWitness);
+
+		assert_ok!(ArchipelModule::force_clear_group(Origin::root(), 1, 2));
+		assert_eq!(ArchipelModule::get_group_members_count(1), 0);
+		assert!(ArchipelModule::group_members(1, None, 10).is_empty());
+		assert!(!ArchipelModule::is_in_group(&10, 1));
+		assert!(!ArchipelModule::get_leaded_group(1));
+		assert_eq!(ArchipelModule::get_leadership_epoch(1), 1);
+		assert!(has_event(crate::Event::GiveUpForced(10, 1, 1)));
+		assert!(has_event(crate::Event::GroupCleared(1, 2)));
+
+		// other groups are kept
+		assert_eq!(ArchipelModule::node_groups(&20, None, 10), vec![2]);
+		assert_eq!(ArchipelModule::get_group_members_count(2), 1);
+	})
+}
@@ -0,0 +1,286 @@
+//! Weights for pallet_archipel
+//!
+//! These weights are hand-written estimates, not benchmark results: each function charges a
+//! base execution cost plus the database reads and writes its extrinsic performs, scaled by the
+//! witness parameters. They should be replaced with weights generated from the pallet benchmarks
+//! once those can be run against the reference hardware.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_archipel.
+pub trait WeightInfo {
+	fn set_leader() -> Weight;
+	fn give_up_leadership() -> Weight;
+	fn add_heartbeat_first() -> Weight;
+	fn add_heartbeat() -> Weight;
+	fn add_heartbeats(g: u32) -> Weight;
+	fn heartbeat() -> Weight;
+	fn add_member() -> Weight;
+	fn remove_member(g: u32, l: u32) -> Weight;
+	fn deregister(g: u32, l: u32) -> Weight;
+	fn force_remove(g: u32, l: u32) -> Weight;
+	fn create_group(n: u32) -> Weight;
+	fn update_group(n: u32) -> Weight;
+	fn remove_group() -> Weight;
+	fn set_keys() -> Weight;
+	fn purge_keys() -> Weight;
+	fn force_set_leader() -> Weight;
+	fn force_give_up() -> Weight;
+	fn force_set_status() -> Weight;
+	fn force_clear_group(m: u32) -> Weight;
+}
+
+/// Weights for pallet_archipel using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	fn set_leader() -> Weight {
+		(40_713_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(8 as Weight))
+			.saturating_add(T::DbWeight::get().writes(6 as Weight))
+	}
+	fn give_up_leadership() -> Weight {
+		(31_046_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn add_heartbeat_first() -> Weight {
+		(55_481_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(7 as Weight))
+			.saturating_add(T::DbWeight::get().writes(8 as Weight))
+	}
+	fn add_heartbeat() -> Weight {
+		(38_256_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(8 as Weight))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+	}
+	fn add_heartbeats(g: u32) -> Weight {
+		(27_364_000 as Weight)
+			.saturating_add((19_622_000 as Weight).saturating_mul(g as Weight))
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().reads((5 as Weight).saturating_mul(g as Weight)))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes((5 as Weight).saturating_mul(g as Weight)))
+	}
+	fn heartbeat() -> Weight {
+		(60_394_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(10 as Weight))
+			.saturating_add(T::DbWeight::get().writes(10 as Weight))
+	}
+	fn add_member() -> Weight {
+		(17_420_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn remove_member(g: u32, l: u32) -> Weight {
+		(52_176_000 as Weight)
+			.saturating_add((9_327_000 as Weight).saturating_mul(g as Weight))
+			.saturating_add((5_812_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(g as Weight)))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(l as Weight)))
+			.saturating_add(T::DbWeight::get().writes(9 as Weight))
+			.saturating_add(T::DbWeight::get().writes((3 as Weight).saturating_mul(g as Weight)))
+			.saturating_add(T::DbWeight::get().writes((3 as Weight).saturating_mul(l as Weight)))
+	}
+	fn deregister(g: u32, l: u32) -> Weight {
+		(48_905_000 as Weight)
+			.saturating_add((9_327_000 as Weight).saturating_mul(g as Weight))
+			.saturating_add((5_812_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(g as Weight)))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(l as Weight)))
+			.saturating_add(T::DbWeight::get().writes(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes((3 as Weight).saturating_mul(g as Weight)))
+			.saturating_add(T::DbWeight::get().writes((3 as Weight).saturating_mul(l as Weight)))
+	}
+	fn force_remove(g: u32, l: u32) -> Weight {
+		(46_441_000 as Weight)
+			.saturating_add((9_327_000 as Weight).saturating_mul(g as Weight))
+			.saturating_add((5_812_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(g as Weight)))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(l as Weight)))
+			.saturating_add(T::DbWeight::get().writes(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes((3 as Weight).saturating_mul(g as Weight)))
+			.saturating_add(T::DbWeight::get().writes((3 as Weight).saturating_mul(l as Weight)))
+	}
+	fn create_group(n: u32) -> Weight {
+		(29_108_000 as Weight)
+			.saturating_add((3_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn update_group(n: u32) -> Weight {
+		(30_512_000 as Weight)
+			.saturating_add((3_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	fn remove_group() -> Weight {
+		(26_874_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn set_keys() -> Weight {
+		(76_318_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
+	fn purge_keys() -> Weight {
+		(24_103_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	fn force_set_leader() -> Weight {
+		(27_645_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(6 as Weight))
+	}
+	fn force_give_up() -> Weight {
+		(22_318_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn force_set_status() -> Weight {
+		(19_872_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn force_clear_group(m: u32) -> Weight {
+		(31_209_000 as Weight)
+			.saturating_add((8_417_000 as Weight).saturating_mul(m as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(m as Weight)))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(m as Weight)))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	fn set_leader() -> Weight {
+		(40_713_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(8 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(6 as Weight))
+	}
+	fn give_up_leadership() -> Weight {
+		(31_046_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn add_heartbeat_first() -> Weight {
+		(55_481_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(7 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(8 as Weight))
+	}
+	fn add_heartbeat() -> Weight {
+		(38_256_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(8 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+	}
+	fn add_heartbeats(g: u32) -> Weight {
+		(27_364_000 as Weight)
+			.saturating_add((19_622_000 as Weight).saturating_mul(g as Weight))
+			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((5 as Weight).saturating_mul(g as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((5 as Weight).saturating_mul(g as Weight)))
+	}
+	fn heartbeat() -> Weight {
+		(60_394_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(10 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(10 as Weight))
+	}
+	fn add_member() -> Weight {
+		(17_420_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn remove_member(g: u32, l: u32) -> Weight {
+		(52_176_000 as Weight)
+			.saturating_add((9_327_000 as Weight).saturating_mul(g as Weight))
+			.saturating_add((5_812_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((3 as Weight).saturating_mul(g as Weight)))
+			.saturating_add(RocksDbWeight::get().reads((2 as Weight).saturating_mul(l as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(9 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((3 as Weight).saturating_mul(g as Weight)))
+			.saturating_add(RocksDbWeight::get().writes((3 as Weight).saturating_mul(l as Weight)))
+	}
+	fn deregister(g: u32, l: u32) -> Weight {
+		(48_905_000 as Weight)
+			.saturating_add((9_327_000 as Weight).saturating_mul(g as Weight))
+			.saturating_add((5_812_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((3 as Weight).saturating_mul(g as Weight)))
+			.saturating_add(RocksDbWeight::get().reads((2 as Weight).saturating_mul(l as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((3 as Weight).saturating_mul(g as Weight)))
+			.saturating_add(RocksDbWeight::get().writes((3 as Weight).saturating_mul(l as Weight)))
+	}
+	fn force_remove(g: u32, l: u32) -> Weight {
+		(46_441_000 as Weight)
+			.saturating_add((9_327_000 as Weight).saturating_mul(g as Weight))
+			.saturating_add((5_812_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((3 as Weight).saturating_mul(g as Weight)))
+			.saturating_add(RocksDbWeight::get().reads((2 as Weight).saturating_mul(l as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((3 as Weight).saturating_mul(g as Weight)))
+			.saturating_add(RocksDbWeight::get().writes((3 as Weight).saturating_mul(l as Weight)))
+	}
+	fn create_group(n: u32) -> Weight {
+		(29_108_000 as Weight)
+			.saturating_add((3_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn update_group(n: u32) -> Weight {
+		(30_512_000 as Weight)
+			.saturating_add((3_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	fn remove_group() -> Weight {
+		(26_874_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn set_keys() -> Weight {
+		(76_318_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
+	}
+	fn purge_keys() -> Weight {
+		(24_103_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	fn force_set_leader() -> Weight {
+		(27_645_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(6 as Weight))
+	}
+	fn force_give_up() -> Weight {
+		(22_318_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn force_set_status() -> Weight {
+		(19_872_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn force_clear_group(m: u32) -> Weight {
+		(31_209_000 as Weight)
+			.saturating_add((8_417_000 as Weight).saturating_mul(m as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((1 as Weight).saturating_mul(m as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((2 as Weight).saturating_mul(m as Weight)))
+	}
+}
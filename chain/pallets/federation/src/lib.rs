@@ -0,0 +1,83 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Dispatch of root calls approved by the federation council.
+//!
+//! Federation chains run without a sudo key, so calls requiring the root origin, like
+//! runtime upgrades, are dispatched by this pallet once approved by `ApproveOrigin`.
+//!
+//! Only calls accepted by `CallFilter` can be dispatched. They bypass the runtime base call
+//! filter and are free of fees, the approval being paid by the council motion.
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::{
+		dispatch::{DispatchResult, GetDispatchInfo, UnfilteredDispatchable},
+		pallet_prelude::*,
+		traits::Filter,
+		weights::Pays,
+	};
+	use frame_system::pallet_prelude::*;
+	use sp_std::prelude::*;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// Because this pallet emits events, it depends on the runtime's definition of an event.
+		type Event: From<Event> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// Call dispatched with the root origin.
+		type Call: Parameter + UnfilteredDispatchable<Origin = Self::Origin> + GetDispatchInfo;
+
+		/// Origin allowed to dispatch calls with the root origin.
+		type ApproveOrigin: EnsureOrigin<Self::Origin>;
+
+		/// Calls that can be dispatched with the root origin.
+		type CallFilter: Filter<<Self as Config>::Call>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event {
+		// Approved call dispatched with its result event
+		Dispatched(DispatchResult),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// Call can't be dispatched with the root origin by the federation.
+		CallFiltered,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		#[pallet::weight({
+			let dispatch_info = call.get_dispatch_info();
+			(dispatch_info.weight.saturating_add(10_000), dispatch_info.class)
+		})]
+		// Dispatch an approved call with the root origin
+		pub fn dispatch_as_root(origin: OriginFor<T>, call: Box<<T as Config>::Call>) -> DispatchResultWithPostInfo {
+			T::ApproveOrigin::ensure_origin(origin)?;
+			ensure!(T::CallFilter::filter(&call), Error::<T>::CallFiltered);
+
+			let result = call.dispatch_bypass_filter(frame_system::RawOrigin::Root.into());
+			Self::deposit_event(Event::Dispatched(result.map(|_| ()).map_err(|e| e.error)));
+
+			// Approval is paid by the council motion
+			Ok(Pays::No.into())
+		}
+	}
+}
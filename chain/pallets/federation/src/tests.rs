@@ -0,0 +1,55 @@
+use crate::mock::*;
+use frame_support::{assert_noop, assert_ok, weights::Pays};
+use sp_runtime::DispatchError;
+
+fn has_event(event: crate::Event) -> bool {
+	System::events().iter().any(|record| record.event == Event::pallet_federation(event.clone()))
+}
+
+#[test]
+fn dispatch_as_root_should_work() {
+	new_test_ext().execute_with(|| {
+		// setting storage requires root
+		let call = Box::new(Call::System(frame_system::Call::set_storage(vec![(b"key".to_vec(), b"value".to_vec())])));
+
+		let post_info = Federation::dispatch_as_root(Origin::signed(Council::get()), call).unwrap();
+		assert_eq!(post_info.pays_fee, Pays::No);
+		assert_eq!(sp_io::storage::get(b"key"), Some(b"value".to_vec()));
+		assert!(has_event(crate::Event::Dispatched(Ok(()))));
+	})
+}
+
+#[test]
+fn dispatch_as_root_should_require_approval() {
+	new_test_ext().execute_with(|| {
+		let call = Box::new(Call::System(frame_system::Call::set_storage(vec![(b"key".to_vec(), b"value".to_vec())])));
+
+		assert_noop!(Federation::dispatch_as_root(Origin::signed(2), call.clone()), DispatchError::BadOrigin);
+		assert_noop!(Federation::dispatch_as_root(Origin::root(), call), DispatchError::BadOrigin);
+	})
+}
+
+#[test]
+fn filtered_call_should_not_be_dispatched() {
+	new_test_ext().execute_with(|| {
+		let call = Box::new(Call::System(frame_system::Call::remark(b"remark".to_vec())));
+
+		assert_noop!(
+			Federation::dispatch_as_root(Origin::signed(Council::get()), call),
+			crate::Error::<Test>::CallFiltered
+		);
+	})
+}
+
+#[test]
+fn failed_call_should_be_reported() {
+	new_test_ext().execute_with(|| {
+		// root origin is not an approval origin
+		let call = Box::new(Call::System(frame_system::Call::set_storage(vec![(b"key".to_vec(), b"value".to_vec())])));
+		let call = Box::new(Call::Federation(crate::Call::dispatch_as_root(call)));
+
+		assert_ok!(Federation::dispatch_as_root(Origin::signed(Council::get()), call));
+		assert!(has_event(crate::Event::Dispatched(Err(DispatchError::BadOrigin))));
+		assert_eq!(sp_io::storage::get(b"key"), None);
+	})
+}
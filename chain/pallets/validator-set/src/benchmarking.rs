@@ -0,0 +1,48 @@
+//! Benchmarking setup for pallet-validator-set
+
+use super::*;
+
+use frame_system::RawOrigin;
+use frame_support::traits::Get;
+use frame_benchmarking::{account, benchmarks, impl_benchmark_test_suite};
+use sp_std::prelude::*;
+#[allow(unused)]
+use crate::Pallet as ValidatorSet;
+
+const SEED: u32 = 0;
+
+// Setting `count` validators for the next sessions
+fn set_validators<T: Config>(count: u32) -> Vec<T::AccountId> {
+	let validators: Vec<T::AccountId> = (0 .. count).map(|index| account("validator", index, SEED)).collect();
+	Validators::<T>::put(&validators);
+	validators
+}
+
+benchmarks! {
+	add_validator {
+		let v in T::MinAuthorities::get() .. T::MaxAuthorities::get() - 1;
+		set_validators::<T>(v);
+		// Worst case: added validator was flagged as offender
+		let validator: T::AccountId = account("new", 0, SEED);
+		Offenders::<T>::insert(&validator, 1);
+	}: _(RawOrigin::Root, validator.clone())
+	verify {
+		assert!(Validators::<T>::get().contains(&validator));
+		assert!(!Offenders::<T>::contains_key(&validator));
+	}
+
+	remove_validator {
+		let v in T::MinAuthorities::get() + 1 .. T::MaxAuthorities::get();
+		// Worst case: removed validator is the last one of the set
+		let validator = set_validators::<T>(v).pop().ok_or("No validator to remove")?;
+	}: _(RawOrigin::Root, validator.clone())
+	verify {
+		assert!(!Validators::<T>::get().contains(&validator));
+	}
+}
+
+impl_benchmark_test_suite!(
+	ValidatorSet,
+	crate::mock::new_test_ext(),
+	crate::mock::Test,
+);
@@ -0,0 +1,327 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! PoA validator set of the archipel chain.
+//!
+//! Federation authorities are added and removed by `AddRemoveOrigin`. Changes are handed
+//! to `pallet_session` as its `SessionManager`, so they are applied at the next session
+//! boundary with the session keys registered by validators through `session.setKeys`.
+//!
+//! Validators reported for an offence, like a GRANDPA equivocation, are flagged and removed
+//! from the next sessions as long as `MinAuthorities` validators remain. Offenders of the
+//! current session are also disabled until its end, unless the session already disabled
+//! `DisabledValidatorsThreshold` of its validators. Offenders must be validators of the current
+//! session or be identified as validators of a past session kept by `pallet_session::historical`.
+
+pub use pallet::*;
+
+use sp_runtime::traits::Convert;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub mod weights;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+	use sp_runtime::{traits::Convert, Perbill};
+	use sp_staking::{
+		offence::{OffenceDetails, OnOffenceHandler},
+		SessionIndex,
+	};
+	use sp_std::prelude::*;
+	use crate::weights::WeightInfo;
+
+	#[pallet::config]
+	pub trait Config:
+		frame_system::Config
+		+ pallet_session::Config<ValidatorId = <Self as frame_system::Config>::AccountId>
+		+ pallet_session::historical::Config
+	{
+		/// Because this pallet emits events, it depends on the runtime's definition of an event.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// Origin allowed to add and remove validators.
+		type AddRemoveOrigin: EnsureOrigin<Self::Origin>;
+
+		/// Minimum number of validators kept in the set.
+		#[pallet::constant]
+		type MinAuthorities: Get<u32>;
+
+		/// Maximum number of validators in the set.
+		#[pallet::constant]
+		type MaxAuthorities: Get<u32>;
+
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	// Validators of the next sessions storage
+	#[pallet::storage]
+	#[pallet::getter(fn validators)]
+	pub(super) type Validators<T: Config> = StorageValue<_, Vec<T::AccountId>, ValueQuery>;
+
+	// Validators changed since the last planned session storage
+	#[pallet::storage]
+	#[pallet::getter(fn validators_changed)]
+	pub(super) type ValidatorsChanged<T> = StorageValue<_, bool, ValueQuery>;
+
+	// Session of the last offence of a validator storage
+	#[pallet::storage]
+	#[pallet::getter(fn offender)]
+	pub(super) type Offenders<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, SessionIndex, OptionQuery>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// Validators of the first session, they must register session keys at genesis.
+		pub validators: Vec<T::AccountId>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { validators: Default::default() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			assert!(
+				self.validators.len() >= T::MinAuthorities::get() as usize,
+				"Initial validators must reach the minimum number of authorities",
+			);
+			assert!(
+				self.validators.len() <= T::MaxAuthorities::get() as usize,
+				"Initial validators must not exceed the maximum number of authorities",
+			);
+
+			// First session is planned with genesis validators
+			<Validators<T>>::put(&self.validators);
+			<ValidatorsChanged<T>>::put(true);
+		}
+	}
+
+	#[pallet::event]
+	#[pallet::metadata(T::AccountId = "AccountId")]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		// Validator added to the next sessions event
+		ValidatorAdded(T::AccountId),
+		// Validator removed from the next sessions event
+		ValidatorRemoved(T::AccountId),
+		// Validator reported for an offence in a session, and whether it was disabled and removed event
+		OffenceReported(T::AccountId, SessionIndex, bool, bool),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// Account is already a validator.
+		AlreadyValidator,
+		/// Account is not a validator.
+		NotValidator,
+		/// Removing the validator would leave less than `MinAuthorities` validators.
+		TooFewValidators,
+		/// Adding the validator would exceed `MaxAuthorities` validators.
+		TooManyValidators,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		#[pallet::weight(T::WeightInfo::add_validator(T::MaxAuthorities::get()))]
+		// Add a validator from the next planned session
+		pub fn add_validator(origin: OriginFor<T>, validator: T::AccountId) -> DispatchResultWithPostInfo {
+			T::AddRemoveOrigin::ensure_origin(origin)?;
+
+			let count = <Validators<T>>::try_mutate(|validators| -> Result<u32, DispatchError> {
+				ensure!(!validators.contains(&validator), Error::<T>::AlreadyValidator);
+				ensure!(validators.len() < T::MaxAuthorities::get() as usize, Error::<T>::TooManyValidators);
+				validators.push(validator.clone());
+				Ok(validators.len() as u32)
+			})?;
+			<ValidatorsChanged<T>>::put(true);
+
+			// Adding back an offender clears its flag
+			<Offenders<T>>::remove(&validator);
+
+			Self::deposit_event(Event::ValidatorAdded(validator));
+
+			Ok(Some(T::WeightInfo::add_validator(count)).into())
+		}
+
+		#[pallet::weight(T::WeightInfo::remove_validator(T::MaxAuthorities::get()))]
+		// Remove a validator from the next planned session
+		pub fn remove_validator(origin: OriginFor<T>, validator: T::AccountId) -> DispatchResultWithPostInfo {
+			T::AddRemoveOrigin::ensure_origin(origin)?;
+
+			let count = <Validators<T>>::try_mutate(|validators| -> Result<u32, DispatchError> {
+				let index = validators.iter().position(|v| *v == validator).ok_or(Error::<T>::NotValidator)?;
+				ensure!(Self::can_remove_validator(validators), Error::<T>::TooFewValidators);
+				validators.remove(index);
+				Ok(validators.len() as u32)
+			})?;
+			<ValidatorsChanged<T>>::put(true);
+
+			Self::deposit_event(Event::ValidatorRemoved(validator));
+
+			Ok(Some(T::WeightInfo::remove_validator(count.saturating_add(1))).into())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		// Checking that a validator can be removed while keeping `MinAuthorities` validators
+		fn can_remove_validator(validators: &[T::AccountId]) -> bool {
+			validators.len() > T::MinAuthorities::get() as usize
+		}
+
+		// Checking that offender was a validator of `session`, weighted by its reads
+		fn was_validator(
+			offender: &T::AccountId,
+			identification: &T::FullIdentification,
+			session: SessionIndex,
+		) -> (bool, Weight) {
+			let found = if session == <pallet_session::Pallet<T>>::current_index() {
+				<pallet_session::Pallet<T>>::validators().contains(offender)
+			} else {
+				// Past sessions are only kept as roots, so the offender must carry the identification
+				// reported to historical sessions, which was proven against the root of `session`
+				<pallet_session::historical::Pallet<T>>::historical_root(session).is_some()
+					&& T::FullIdentificationOf::convert(offender.clone()).as_ref() == Some(identification)
+			};
+			(found, T::DbWeight::get().reads(2))
+		}
+
+		// Disabling an offender until the end of the current session, keeping disabled validators
+		// within `DisabledValidatorsThreshold` of the session validators
+		fn disable_offender(offender: &T::AccountId, session: SessionIndex) -> bool {
+			if session != <pallet_session::Pallet<T>>::current_index() {
+				return false;
+			}
+
+			let validators = <pallet_session::Pallet<T>>::validators();
+			let disabled = <pallet_session::Pallet<T>>::disabled_validators();
+			let threshold = T::DisabledValidatorsThreshold::get() * validators.len() as u32;
+			match validators.iter().position(|v| v == offender) {
+				Some(index) if !disabled.contains(&(index as u32)) && (disabled.len() as u32) < threshold => {
+					<pallet_session::Pallet<T>>::disable_index(index);
+					true
+				}
+				_ => false,
+			}
+		}
+
+		// Removing an offender unless the minimum number of validators is reached
+		fn remove_offender(offender: &T::AccountId) -> bool {
+			<Validators<T>>::mutate(|validators| {
+				match validators.iter().position(|v| v == offender) {
+					Some(index) if Self::can_remove_validator(validators) => {
+						validators.remove(index);
+						true
+					}
+					_ => false,
+				}
+			})
+		}
+	}
+
+	impl<T: Config, Reporter>
+		OnOffenceHandler<Reporter, (T::AccountId, T::FullIdentification), Weight> for Pallet<T>
+	{
+		fn on_offence(
+			offenders: &[OffenceDetails<Reporter, (T::AccountId, T::FullIdentification)>],
+			_slash_fraction: &[Perbill],
+			session: SessionIndex,
+		) -> Result<Weight, ()> {
+			let db_weight = T::DbWeight::get();
+			let mut weight: Weight = 0;
+			let mut changed = false;
+			for details in offenders {
+				let (offender, identification) = &details.offender;
+
+				// Accounts outside of the session set can't be flagged or removed
+				let (was_validator, check_weight) = Self::was_validator(offender, identification, session);
+				weight = weight.saturating_add(check_weight);
+				if !was_validator {
+					continue;
+				}
+
+				<Offenders<T>>::insert(offender, session);
+				let disabled = Self::disable_offender(offender, session);
+				let removed = Self::remove_offender(offender);
+				changed |= removed;
+				weight = weight.saturating_add(db_weight.reads_writes(4, 3));
+
+				Self::deposit_event(Event::OffenceReported(offender.clone(), session, disabled, removed));
+			}
+
+			if changed {
+				<ValidatorsChanged<T>>::put(true);
+				weight = weight.saturating_add(db_weight.writes(1));
+			}
+
+			Ok(weight)
+		}
+
+		fn can_report() -> bool {
+			true
+		}
+	}
+
+	impl<T: Config> pallet_session::SessionManager<T::AccountId> for Pallet<T> {
+		// Session keeps its validators unless the set changed
+		fn new_session(_new_index: SessionIndex) -> Option<Vec<T::AccountId>> {
+			if <ValidatorsChanged<T>>::take() {
+				Some(Self::validators())
+			} else {
+				None
+			}
+		}
+
+		fn end_session(_end_index: SessionIndex) {}
+
+		fn start_session(_start_index: SessionIndex) {}
+	}
+
+	// Validators have no exposure, they are identified as `FullIdentificationOf` reports them
+	impl<T: Config> pallet_session::historical::SessionManager<T::AccountId, T::FullIdentification> for Pallet<T> {
+		fn new_session(new_index: SessionIndex) -> Option<Vec<(T::AccountId, T::FullIdentification)>> {
+			<Self as pallet_session::SessionManager<_>>::new_session(new_index).map(|validators| {
+				validators
+					.into_iter()
+					.filter_map(|v| T::FullIdentificationOf::convert(v.clone()).map(|id| (v, id)))
+					.collect()
+			})
+		}
+
+		fn end_session(end_index: SessionIndex) {
+			<Self as pallet_session::SessionManager<_>>::end_session(end_index)
+		}
+
+		fn start_session(start_index: SessionIndex) {
+			<Self as pallet_session::SessionManager<_>>::start_session(start_index)
+		}
+	}
+}
+
+/// Full identification of a validator for historical session proofs.
+pub struct FullIdentificationOf;
+
+impl<AccountId> Convert<AccountId, Option<()>> for FullIdentificationOf {
+	fn convert(_validator: AccountId) -> Option<()> {
+		Some(())
+	}
+}
@@ -0,0 +1,175 @@
+use crate::{Error, mock::*, weights::WeightInfo};
+use frame_support::{assert_noop, assert_ok};
+use pallet_session::SessionManager;
+use sp_runtime::{DispatchError, Perbill};
+use sp_staking::offence::{OffenceDetails, OnOffenceHandler};
+
+fn has_event(event: crate::Event<Test>) -> bool {
+	System::events().iter().any(|record| record.event == Event::pallet_validator_set(event.clone()))
+}
+
+#[test]
+fn genesis_validators_should_start_first_session() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(ValidatorSet::validators(), vec![1, 2]);
+		assert_eq!(Session::validators(), vec![1, 2]);
+		// Unchanged set keeps current validators
+		assert_eq!(ValidatorSet::new_session(2), None);
+	})
+}
+
+#[test]
+fn add_validator_should_work() {
+	new_test_ext().execute_with(|| {
+		ValidatorSet::new_session(0);
+
+		assert_noop!(ValidatorSet::add_validator(Origin::signed(1), 3), DispatchError::BadOrigin);
+		assert_noop!(ValidatorSet::add_validator(Origin::root(), 2), Error::<Test>::AlreadyValidator);
+
+		assert_ok!(ValidatorSet::add_validator(Origin::root(), 3));
+		assert!(has_event(crate::Event::ValidatorAdded(3)));
+
+		// Validator is applied at the next session
+		assert_eq!(ValidatorSet::new_session(1), Some(vec![1, 2, 3]));
+		assert_eq!(ValidatorSet::new_session(2), None);
+	})
+}
+
+#[test]
+fn remove_validator_should_work() {
+	new_test_ext().execute_with(|| {
+		ValidatorSet::new_session(0);
+		assert_ok!(ValidatorSet::add_validator(Origin::root(), 3));
+
+		assert_noop!(ValidatorSet::remove_validator(Origin::signed(1), 3), DispatchError::BadOrigin);
+		assert_noop!(ValidatorSet::remove_validator(Origin::root(), 4), Error::<Test>::NotValidator);
+
+		assert_ok!(ValidatorSet::remove_validator(Origin::root(), 1));
+		assert!(has_event(crate::Event::ValidatorRemoved(1)));
+		assert_eq!(ValidatorSet::new_session(1), Some(vec![2, 3]));
+
+		// Minimum number of authorities is kept
+		assert_noop!(ValidatorSet::remove_validator(Origin::root(), 2), Error::<Test>::TooFewValidators);
+	})
+}
+
+#[test]
+fn maximum_number_of_validators_should_be_kept() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(ValidatorSet::add_validator(Origin::root(), 3));
+		assert_ok!(ValidatorSet::add_validator(Origin::root(), 4));
+
+		assert_noop!(ValidatorSet::add_validator(Origin::root(), 5), Error::<Test>::TooManyValidators);
+
+		// Actual number of validators is charged
+		let info = ValidatorSet::remove_validator(Origin::root(), 4).unwrap();
+		assert_eq!(info.actual_weight, Some(<() as WeightInfo>::remove_validator(4)));
+		let info = ValidatorSet::add_validator(Origin::root(), 5).unwrap();
+		assert_eq!(info.actual_weight, Some(<() as WeightInfo>::add_validator(4)));
+	})
+}
+
+fn report_identified_offence(offender: u64, identification: u64, session: u32) {
+	let details = OffenceDetails { offender: (offender, identification), reporters: vec![] };
+	assert!(
+		<ValidatorSet as OnOffenceHandler<u64, (u64, u64), _>>::on_offence(&[details], &[Perbill::zero()], session)
+			.is_ok()
+	);
+}
+
+fn report_offence(offender: u64, session: u32) {
+	report_identified_offence(offender, offender, session);
+}
+
+#[test]
+fn offender_should_be_removed() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(ValidatorSet::add_validator(Origin::root(), 3));
+		// Validator is planned by the first rotation and active from the second one
+		Session::rotate_session();
+		Session::rotate_session();
+		assert_eq!(Session::validators(), vec![1, 2, 3]);
+
+		report_offence(3, 2);
+		assert_eq!(ValidatorSet::offender(3), Some(2));
+		assert!(has_event(crate::Event::OffenceReported(3, 2, true, true)));
+		// Offender is disabled for the rest of the current session
+		assert_eq!(Session::disabled_validators(), vec![2]);
+		assert_eq!(ValidatorSet::new_session(4), Some(vec![1, 2]));
+
+		// Offender added back by the admin is not flagged anymore
+		assert_ok!(ValidatorSet::add_validator(Origin::root(), 3));
+		assert_eq!(ValidatorSet::offender(3), None);
+	})
+}
+
+#[test]
+fn offender_outside_of_session_set_should_be_ignored() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(ValidatorSet::add_validator(Origin::root(), 3));
+
+		// 3 is not a validator of the current session yet
+		report_offence(3, 0);
+		assert_eq!(ValidatorSet::offender(3), None);
+		assert!(!has_event(crate::Event::OffenceReported(3, 0, false, true)));
+		assert!(ValidatorSet::validators().contains(&3));
+
+		// session unknown to historical data
+		report_offence(1, 10);
+		assert_eq!(ValidatorSet::offender(1), None);
+	})
+}
+
+#[test]
+fn past_offender_should_be_checked_against_its_identification() {
+	new_test_ext().execute_with(|| {
+		Session::rotate_session();
+		Session::rotate_session();
+		assert_eq!(Session::current_index(), 2);
+
+		// 2 is not identified as 1 in historical sessions
+		report_identified_offence(2, 1, 1);
+		assert_eq!(ValidatorSet::offender(2), None);
+		assert_eq!(ValidatorSet::offender(1), None);
+
+		report_identified_offence(2, 2, 1);
+		assert_eq!(ValidatorSet::offender(2), Some(1));
+		// Past offenders are not disabled in the current session
+		assert!(has_event(crate::Event::OffenceReported(2, 1, false, false)));
+		assert!(Session::disabled_validators().is_empty());
+	})
+}
+
+#[test]
+fn disabled_validators_threshold_should_be_kept() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(ValidatorSet::add_validator(Origin::root(), 3));
+		assert_ok!(ValidatorSet::add_validator(Origin::root(), 4));
+		Session::rotate_session();
+		Session::rotate_session();
+		assert_eq!(Session::validators(), vec![1, 2, 3, 4]);
+
+		// a third of 4 validators can be disabled
+		report_offence(3, 2);
+		assert!(has_event(crate::Event::OffenceReported(3, 2, true, true)));
+		report_offence(4, 2);
+		assert!(has_event(crate::Event::OffenceReported(4, 2, false, true)));
+		assert_eq!(Session::disabled_validators(), vec![2]);
+
+		// offenders are still removed from the next sessions
+		assert_eq!(ValidatorSet::validators(), vec![1, 2]);
+	})
+}
+
+#[test]
+fn offender_should_be_flagged_when_minimum_is_reached() {
+	new_test_ext().execute_with(|| {
+		ValidatorSet::new_session(0);
+
+		report_offence(1, 1);
+		assert_eq!(ValidatorSet::offender(1), Some(1));
+		assert!(has_event(crate::Event::OffenceReported(1, 1, false, false)));
+		assert_eq!(ValidatorSet::validators(), vec![1, 2]);
+		assert_eq!(ValidatorSet::new_session(2), None);
+	})
+}
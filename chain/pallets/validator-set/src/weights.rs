@@ -0,0 +1,51 @@
+//! Weights for pallet_validator_set
+//!
+//! These weights are hand-written estimates, not benchmark results: each function charges a
+//! base execution cost plus the database reads and writes its extrinsic performs, scaled by the
+//! witness parameters. They should be replaced with weights generated from the pallet benchmarks
+//! once those can be run against the reference hardware.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_validator_set.
+pub trait WeightInfo {
+	fn add_validator(v: u32) -> Weight;
+	fn remove_validator(v: u32) -> Weight;
+}
+
+/// Weights for pallet_validator_set using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	fn add_validator(v: u32) -> Weight {
+		(24_137_000 as Weight)
+			.saturating_add((96_000 as Weight).saturating_mul(v as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn remove_validator(v: u32) -> Weight {
+		(22_806_000 as Weight)
+			.saturating_add((104_000 as Weight).saturating_mul(v as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	fn add_validator(v: u32) -> Weight {
+		(24_137_000 as Weight)
+			.saturating_add((96_000 as Weight).saturating_mul(v as Weight))
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn remove_validator(v: u32) -> Weight {
+		(22_806_000 as Weight)
+			.saturating_add((104_000 as Weight).saturating_mul(v as Weight))
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+}
@@ -7,14 +7,16 @@
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
 use sp_std::prelude::*;
-use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
+use sp_core::{crypto::KeyTypeId, u32_trait::{_2, _3}, OpaqueMetadata};
 use sp_runtime::{
 	ApplyExtrinsicResult, generic, create_runtime_str, impl_opaque_keys, MultiSignature,
-	transaction_validity::{TransactionValidity, TransactionSource},
+	transaction_validity::{TransactionPriority, TransactionValidity, TransactionSource},
 };
 use sp_runtime::traits::{
 	BlakeTwo256, Block as BlockT, AccountIdLookup, Verify, IdentifyAccount, NumberFor,
+	ConvertInto, OpaqueKeys,
 };
+use codec::Encode;
 use sp_api::impl_runtime_apis;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use pallet_grandpa::{AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList};
@@ -31,13 +33,15 @@ pub use pallet_balances::Call as BalancesCall;
 pub use sp_runtime::{Permill, Perbill};
 pub use frame_support::{
 	construct_runtime, parameter_types, StorageValue,
-	traits::{KeyOwnerProofSystem, Randomness},
+	traits::{Filter, KeyOwnerProofSystem, Randomness},
 	weights::{
 		Weight, IdentityFee,
 		constants::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight, WEIGHT_PER_SECOND},
 	},
 };
 use pallet_transaction_payment::CurrencyAdapter;
+use frame_system::{EnsureOneOf, EnsureRoot};
+use pallet_session::historical as pallet_session_historical;
 
 /// Import the archipel pallet.
 pub use pallet_archipel;
@@ -88,6 +92,7 @@ pub mod opaque {
 		pub struct SessionKeys {
 			pub aura: Aura,
 			pub grandpa: Grandpa,
+			pub archipel: ArchipelModule,
 		}
 	}
 }
@@ -103,10 +108,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
 	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
 	//   the compatible custom types.
-	spec_version: 100,
+	spec_version: 101,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 2,
 };
 
 /// This determines the average expected block time that we are targeting.
@@ -201,6 +206,58 @@ impl frame_system::Config for Runtime {
 	type OnSetCode = ();
 }
 
+parameter_types! {
+	/// Validator set changes are applied every 10 minutes.
+	pub const SessionPeriod: BlockNumber = 10 * MINUTES;
+	pub const SessionOffset: BlockNumber = 0;
+	pub const DisabledValidatorsThreshold: Perbill = Perbill::from_percent(33);
+	/// A federation keeps at least one authority.
+	pub const MinAuthorities: u32 = 1;
+	/// Authorities are federation operators, a hundred of them is far above expected sets.
+	pub const MaxAuthorities: u32 = 100;
+}
+
+impl pallet_session::Config for Runtime {
+	type Event = Event;
+	type ValidatorId = AccountId;
+	type ValidatorIdOf = ConvertInto;
+	type ShouldEndSession = pallet_session::PeriodicSessions<SessionPeriod, SessionOffset>;
+	type NextSessionRotation = pallet_session::PeriodicSessions<SessionPeriod, SessionOffset>;
+	type SessionManager = pallet_session::historical::NoteHistoricalRoot<Self, ValidatorSet>;
+	type SessionHandler = <opaque::SessionKeys as OpaqueKeys>::KeyTypeIdProviders;
+	type Keys = opaque::SessionKeys;
+	type DisabledValidatorsThreshold = DisabledValidatorsThreshold;
+	type WeightInfo = pallet_session::weights::SubstrateWeight<Runtime>;
+}
+
+impl pallet_session::historical::Config for Runtime {
+	type FullIdentification = ();
+	type FullIdentificationOf = pallet_validator_set::FullIdentificationOf;
+}
+
+parameter_types! {
+	/// Equivocation reports are valid for a whole session.
+	pub ReportLongevity: u64 = SessionPeriod::get() as u64;
+	pub OffencesWeightSoftLimit: Weight = Perbill::from_percent(60) * BlockWeights::get().max_block;
+}
+
+/// Reported authorities are flagged and removed from the validator set.
+impl pallet_offences::Config for Runtime {
+	type Event = Event;
+	type IdentificationTuple = pallet_session::historical::IdentificationTuple<Self>;
+	type OnOffenceHandler = ValidatorSet;
+	type WeightSoftLimit = OffencesWeightSoftLimit;
+}
+
+/// Authorities are added and removed by the admin or the federation council.
+impl pallet_validator_set::Config for Runtime {
+	type Event = Event;
+	type AddRemoveOrigin = EnsureRootOrFederationApproval;
+	type MinAuthorities = MinAuthorities;
+	type MaxAuthorities = MaxAuthorities;
+	type WeightInfo = pallet_validator_set::weights::SubstrateWeight<Runtime>;
+}
+
 impl pallet_aura::Config for Runtime {
 	type AuthorityId = AuraId;
 }
@@ -209,7 +266,7 @@ impl pallet_grandpa::Config for Runtime {
 	type Event = Event;
 	type Call = Call;
 
-	type KeyOwnerProofSystem = ();
+	type KeyOwnerProofSystem = Historical;
 
 	type KeyOwnerProof =
 		<Self::KeyOwnerProofSystem as KeyOwnerProofSystem<(KeyTypeId, GrandpaId)>>::Proof;
@@ -219,7 +276,8 @@ impl pallet_grandpa::Config for Runtime {
 		GrandpaId,
 	)>>::IdentificationTuple;
 
-	type HandleEquivocation = ();
+	type HandleEquivocation =
+		pallet_grandpa::EquivocationHandler<Self::KeyOwnerIdentification, Offences, ReportLongevity>;
 
 	type WeightInfo = ();
 }
@@ -269,9 +327,110 @@ impl pallet_sudo::Config for Runtime {
 	type Call = Call;
 }
 
+parameter_types! {
+	/// Council motions can be voted for a day.
+	pub const CouncilMotionDuration: BlockNumber = DAYS;
+	pub const CouncilMaxProposals: u32 = 100;
+	pub const CouncilMaxMembers: u32 = 100;
+}
+
+/// Collective of federation operators.
+type FederationCollective = pallet_collective::Instance1;
+
+/// Root or at least two thirds of the federation council.
+pub type EnsureRootOrFederationApproval = EnsureOneOf<
+	AccountId,
+	EnsureRoot<AccountId>,
+	pallet_collective::EnsureProportionAtLeast<_2, _3, AccountId, FederationCollective>,
+>;
+
+impl pallet_collective::Config<FederationCollective> for Runtime {
+	type Origin = Origin;
+	type Proposal = Call;
+	type Event = Event;
+	type MotionDuration = CouncilMotionDuration;
+	type MaxProposals = CouncilMaxProposals;
+	type MaxMembers = CouncilMaxMembers;
+	type DefaultVote = pallet_collective::PrimeDefaultVote;
+	type WeightInfo = pallet_collective::weights::SubstrateWeight<Runtime>;
+}
+
+/// Council members are managed by the council itself.
+impl pallet_membership::Config<pallet_membership::Instance1> for Runtime {
+	type Event = Event;
+	type AddOrigin = EnsureRootOrFederationApproval;
+	type RemoveOrigin = EnsureRootOrFederationApproval;
+	type SwapOrigin = EnsureRootOrFederationApproval;
+	type ResetOrigin = EnsureRootOrFederationApproval;
+	type PrimeOrigin = EnsureRootOrFederationApproval;
+	type MembershipInitialized = Council;
+	type MembershipChanged = Council;
+	type MaxMembers = CouncilMaxMembers;
+	type WeightInfo = pallet_membership::weights::SubstrateWeight<Runtime>;
+}
+
+/// Calls the council can dispatch as root: runtime upgrades, validator set and finality
+/// recovery, council membership and archipel administration.
+pub struct FederationRootCalls;
+impl Filter<Call> for FederationRootCalls {
+	fn filter(call: &Call) -> bool {
+		matches!(
+			call,
+			Call::System(_) | Call::ValidatorSet(_) | Call::Grandpa(_) |
+			Call::CouncilMembership(_) | Call::ArchipelModule(_)
+		)
+	}
+}
+
+/// Council approved calls, like runtime upgrades, are dispatched as root.
+impl pallet_federation::Config for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type ApproveOrigin = EnsureRootOrFederationApproval;
+	type CallFilter = FederationRootCalls;
+}
+
+parameter_types! {
+	/// A leader without heartbeat for 12 blocks (about 1 minute) can be replaced.
+	pub const AliveTime: BlockNumber = 12;
+	/// Leadership must be renewed by leader heartbeats at least every 2 minutes.
+	pub const LeaseDuration: BlockNumber = 2 * MINUTES;
+	/// Group names are limited to 64 bytes.
+	pub const MaxGroupNameLength: u32 = 64;
+	/// Nodes without heartbeat for a week are pruned.
+	pub const PruneAfter: BlockNumber = 7 * DAYS;
+	/// Offchain workers send a heartbeat every 4 blocks, well within `AliveTime`.
+	pub const HeartbeatInterval: BlockNumber = 4;
+	pub const ArchipelUnsignedPriority: TransactionPriority = TransactionPriority::max_value();
+	/// Nodes serving a group before the membership allowlist are enough to run the federation.
+	pub ArchipelMigrationMembers: Vec<AccountId> = Vec::new();
+}
+
 /// Configure the pallet-archipel in pallets/archipel.
 impl pallet_archipel::Config for Runtime {
 	type Event = Event;
+	type AuthorityId = pallet_archipel::crypto::ArchipelAuthId;
+	type AdminOrigin = EnsureRootOrFederationApproval;
+	type AliveTime = AliveTime;
+	type LeaseDuration = LeaseDuration;
+	type MaxGroupNameLength = MaxGroupNameLength;
+	type PruneAfter = PruneAfter;
+	type HeartbeatInterval = HeartbeatInterval;
+	type MigrationMembers = ArchipelMigrationMembers;
+	type UnsignedPriority = ArchipelUnsignedPriority;
+	type WeightInfo = pallet_archipel::weights::SubstrateWeight<Runtime>;
+}
+
+impl frame_system::offchain::SigningTypes for Runtime {
+	type Public = <Signature as Verify>::Signer;
+	type Signature = Signature;
+}
+
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Runtime where
+	Call: From<C>,
+{
+	type OverarchingCall = Call;
+	type Extrinsic = UncheckedExtrinsic;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -284,13 +443,21 @@ construct_runtime!(
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
 		RandomnessCollectiveFlip: pallet_randomness_collective_flip::{Pallet, Call, Storage},
 		Timestamp: pallet_timestamp::{Pallet, Call, Storage, Inherent},
+		// Validator set and session must be initialized before session key handlers.
+		ValidatorSet: pallet_validator_set::{Pallet, Call, Storage, Config<T>, Event<T>},
+		Session: pallet_session::{Pallet, Call, Storage, Config<T>, Event},
+		Historical: pallet_session_historical::{Pallet},
+		Offences: pallet_offences::{Pallet, Call, Storage, Event},
 		Aura: pallet_aura::{Pallet, Config<T>},
-		Grandpa: pallet_grandpa::{Pallet, Call, Storage, Config, Event},
+		Grandpa: pallet_grandpa::{Pallet, Call, Storage, Config, Event, ValidateUnsigned},
 		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
 		TransactionPayment: pallet_transaction_payment::{Pallet, Storage},
 		Sudo: pallet_sudo::{Pallet, Call, Config<T>, Storage, Event<T>},
+		Council: pallet_collective::<Instance1>::{Pallet, Call, Storage, Origin<T>, Event<T>, Config<T>},
+		CouncilMembership: pallet_membership::<Instance1>::{Pallet, Call, Storage, Event<T>, Config<T>},
+		Federation: pallet_federation::{Pallet, Call, Event},
 		// Include the custom logic from the pallet-archipel in the runtime.
-		ArchipelModule: pallet_archipel::{Pallet, Call, Storage, Event<T>},
+		ArchipelModule: pallet_archipel::{Pallet, Call, Config<T>, Storage, Event<T>, ValidateUnsigned},
 	}
 );
 
@@ -316,6 +483,8 @@ pub type SignedExtra = (
 );
 /// Unchecked extrinsic type as expected by this runtime.
 pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;
+/// The payload being signed in transactions.
+pub type SignedPayload = generic::SignedPayload<Call, SignedExtra>;
 /// Extrinsic type that has already been checked.
 pub type CheckedExtrinsic = generic::CheckedExtrinsic<AccountId, Call, SignedExtra>;
 /// Executive: handles dispatch to the various modules.
@@ -416,23 +585,27 @@ impl_runtime_apis! {
 		}
 
 		fn submit_report_equivocation_unsigned_extrinsic(
-			_equivocation_proof: fg_primitives::EquivocationProof<
+			equivocation_proof: fg_primitives::EquivocationProof<
 				<Block as BlockT>::Hash,
 				NumberFor<Block>,
 			>,
-			_key_owner_proof: fg_primitives::OpaqueKeyOwnershipProof,
+			key_owner_proof: fg_primitives::OpaqueKeyOwnershipProof,
 		) -> Option<()> {
-			None
+			let key_owner_proof = key_owner_proof.decode()?;
+
+			Grandpa::submit_unsigned_equivocation_report(
+				equivocation_proof,
+				key_owner_proof,
+			)
 		}
 
 		fn generate_key_ownership_proof(
 			_set_id: fg_primitives::SetId,
-			_authority_id: GrandpaId,
+			authority_id: GrandpaId,
 		) -> Option<fg_primitives::OpaqueKeyOwnershipProof> {
-			// NOTE: this is the only implementation possible since we've
-			// defined our key owner proof type as a bottom type (i.e. a type
-			// with no values).
-			None
+			Historical::prove((fg_primitives::KEY_TYPE, authority_id))
+				.map(|p| p.encode())
+				.map(fg_primitives::OpaqueKeyOwnershipProof::new)
 		}
 	}
 
@@ -442,6 +615,56 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_archipel_rpc_runtime_api::ArchipelApi<Block, AccountId, BlockNumber> for Runtime {
+		fn group_state(group_id: u32) -> pallet_archipel_rpc_runtime_api::GroupState<AccountId, BlockNumber> {
+			ArchipelModule::group_state(group_id)
+		}
+
+		fn leadership(group_id: u32) -> pallet_archipel_rpc_runtime_api::Leadership<AccountId, BlockNumber> {
+			ArchipelModule::leadership(group_id)
+		}
+
+		fn leadership_keys(group_id: u32) -> Vec<Vec<u8>> {
+			ArchipelModule::leadership_keys(group_id)
+		}
+
+		fn recommended_action(
+			account: AccountId,
+			group_id: u32,
+		) -> pallet_archipel_rpc_runtime_api::Recommendation<AccountId> {
+			ArchipelModule::recommended_action(&account, group_id)
+		}
+
+		fn group_events(group_id: u32) -> Vec<pallet_archipel_rpc_runtime_api::GroupEvent<AccountId, BlockNumber>> {
+			System::events()
+				.into_iter()
+				.filter_map(|record| match record.event {
+					Event::pallet_archipel(event) => ArchipelModule::group_event(&event, group_id),
+					_ => None,
+				})
+				.collect()
+		}
+
+		fn group_members(
+			group_id: u32,
+			starting_after: Option<AccountId>,
+			limit: u32,
+		) -> Vec<pallet_archipel_rpc_runtime_api::GroupMemberState<AccountId, BlockNumber>> {
+			ArchipelModule::group_members(group_id, starting_after, limit)
+		}
+
+		fn node_groups(account: AccountId, starting_after: Option<u32>, limit: u32) -> Vec<u32> {
+			ArchipelModule::node_groups(&account, starting_after, limit)
+		}
+
+		fn registered_groups(
+			starting_after: Option<u32>,
+			limit: u32,
+		) -> Vec<(u32, pallet_archipel_rpc_runtime_api::GroupInfo<AccountId, BlockNumber, Vec<u8>>)> {
+			ArchipelModule::registered_groups(starting_after, limit)
+		}
+	}
+
 	impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<Block, Balance> for Runtime {
 		fn query_info(
 			uxt: <Block as BlockT>::Extrinsic,
@@ -485,8 +708,10 @@ impl_runtime_apis! {
 
 			add_benchmark!(params, batches, frame_system, SystemBench::<Runtime>);
 			add_benchmark!(params, batches, pallet_balances, Balances);
+			add_benchmark!(params, batches, pallet_collective, Council);
 			add_benchmark!(params, batches, pallet_timestamp, Timestamp);
 			add_benchmark!(params, batches, pallet_archipel, ArchipelModule);
+			add_benchmark!(params, batches, pallet_validator_set, ValidatorSet);
 
 			if batches.is_empty() { return Err("Benchmark not found for this pallet.".into()) }
 			Ok(batches)
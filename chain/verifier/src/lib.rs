@@ -0,0 +1,249 @@
+//! Verifier of archipel leadership proofs.
+//!
+//! A leadership proof is a storage read proof of the leadership of a group, taken at
+//! a block finalized by GRANDPA, along with the block header and its justification.
+//! Anyone knowing the GRANDPA authority set can check from the proof who is the
+//! finalized leader of a group without trusting the node that produced it.
+//!
+//! A valid proof stays valid forever, so verifiers give the lowest block number and
+//! leadership epoch they accept to reject older proofs replayed after a leader change.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod tests;
+
+use codec::{Decode, Encode};
+use finality_grandpa::Message;
+use sp_finality_grandpa::{check_message_signature, AuthorityId, AuthorityList, AuthoritySignature, SetId};
+use sp_io::hashing::{twox_128, twox_64};
+use sp_runtime::{traits::Header as HeaderT, RuntimeDebug};
+use sp_std::{collections::{btree_map::BTreeMap, btree_set::BTreeSet}, prelude::*};
+use sp_trie::{read_trie_value, Layout, StorageProof};
+
+/// GRANDPA commit for a block.
+pub type Commit<Header> = finality_grandpa::Commit<
+	<Header as HeaderT>::Hash,
+	<Header as HeaderT>::Number,
+	AuthoritySignature,
+	AuthorityId,
+>;
+
+/// GRANDPA justification, encoded as stored and broadcast by nodes.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct GrandpaJustification<Header: HeaderT> {
+	/// Round the block was finalized in.
+	pub round: u64,
+	/// Commit of authorities precommits.
+	pub commit: Commit<Header>,
+	/// Headers between the finalized block and precommits targets.
+	pub votes_ancestries: Vec<Header>,
+}
+
+/// GRANDPA authority set a justification is checked against.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct AuthoritySet {
+	/// Id of the authority set.
+	pub set_id: SetId,
+	/// Authorities with their weight.
+	pub authorities: AuthorityList,
+}
+
+/// Proof of a group leadership at a finalized block.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct LeadershipProof<Header> {
+	/// Header of the finalized block.
+	pub header: Header,
+	/// Encoded GRANDPA justification of the block.
+	pub justification: Vec<u8>,
+	/// Trie nodes proving leadership storage values against the block state root.
+	pub proof: Vec<Vec<u8>>,
+}
+
+/// Leadership of a group proven at a finalized block.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct VerifiedLeadership<AccountId, BlockNumber, Hash> {
+	/// Finalized block number.
+	pub block_number: BlockNumber,
+	/// Finalized block hash.
+	pub block_hash: Hash,
+	/// Leader if the group is leaded.
+	pub leader: Option<AccountId>,
+	/// Leadership epoch.
+	pub epoch: u64,
+}
+
+/// Leadership proof verification error.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Error {
+	/// Justification can't be decoded.
+	InvalidJustification,
+	/// Justification doesn't finalize the proven block.
+	JustificationTargetMismatch,
+	/// Precommit signed by an authority outside of the set.
+	UnknownAuthority,
+	/// Authority precommitted several times.
+	DuplicatePrecommit,
+	/// Precommit signature is invalid.
+	InvalidSignature,
+	/// Precommit target doesn't descend from the proven block.
+	InvalidAncestry,
+	/// Precommits don't reach the supermajority of the authority set.
+	NotEnoughWeight,
+	/// Storage proof doesn't match block state root.
+	InvalidStorageProof,
+	/// Proven storage value can't be decoded.
+	InvalidStorageValue,
+	/// Proven leader is not the expected account.
+	NotLeader,
+	/// Proof is taken before the minimum block or epoch.
+	StaleProof,
+}
+
+// Storage key of a `Twox64Concat` map entry
+fn map_key(pallet: &[u8], item: &[u8], group_id: u32) -> Vec<u8> {
+	let encoded_group = group_id.encode();
+	let mut key = Vec::with_capacity(32 + 8 + encoded_group.len());
+	key.extend_from_slice(&twox_128(pallet));
+	key.extend_from_slice(&twox_128(item));
+	key.extend_from_slice(&twox_64(&encoded_group));
+	key.extend_from_slice(&encoded_group);
+	key
+}
+
+/// Storage keys of `Leaders`, `LeadershipEpochs` and `LeadedGroup` entries of a group.
+///
+/// `pallet` is the name of pallet-archipel in the runtime.
+pub fn leadership_keys(pallet: &[u8], group_id: u32) -> Vec<Vec<u8>> {
+	sp_std::vec![
+		map_key(pallet, b"Leaders", group_id),
+		map_key(pallet, b"LeadershipEpochs", group_id),
+		map_key(pallet, b"LeadedGroup", group_id),
+	]
+}
+
+/// Check that a GRANDPA justification finalizes the header for the authority set.
+pub fn verify_justification<Header: HeaderT>(
+	header: &Header,
+	encoded_justification: &[u8],
+	set: &AuthoritySet,
+) -> Result<(), Error> {
+	let justification = GrandpaJustification::<Header>::decode(&mut &encoded_justification[..])
+		.map_err(|_| Error::InvalidJustification)?;
+
+	let hash = header.hash();
+	if justification.commit.target_hash != hash || justification.commit.target_number != *header.number() {
+		return Err(Error::JustificationTargetMismatch);
+	}
+
+	let ancestries: BTreeMap<Header::Hash, Header::Hash> = justification.votes_ancestries
+		.iter()
+		.map(|ancestry| (ancestry.hash(), *ancestry.parent_hash()))
+		.collect();
+
+	let mut signers = BTreeSet::new();
+	let mut weight: u64 = 0;
+	for signed in justification.commit.precommits.iter() {
+		let authority_weight = set.authorities
+			.iter()
+			.find(|(id, _)| id == &signed.id)
+			.map(|(_, weight)| *weight)
+			.ok_or(Error::UnknownAuthority)?;
+
+		if !signers.insert(signed.id.clone()) {
+			return Err(Error::DuplicatePrecommit);
+		}
+
+		let message = Message::Precommit(signed.precommit.clone());
+		if !check_message_signature(&message, &signed.id, &signed.signature, justification.round, set.set_id) {
+			return Err(Error::InvalidSignature);
+		}
+
+		// Precommit on a descendant also finalizes the block
+		let mut current = signed.precommit.target_hash;
+		while current != hash {
+			current = *ancestries.get(&current).ok_or(Error::InvalidAncestry)?;
+		}
+
+		weight = weight.saturating_add(authority_weight);
+	}
+
+	// More than two thirds of the total weight is required
+	let total_weight = set.authorities.iter().fold(0u64, |total, (_, weight)| total.saturating_add(*weight));
+	let threshold = total_weight - total_weight.saturating_sub(1) / 3;
+	if total_weight == 0 || weight < threshold {
+		return Err(Error::NotEnoughWeight);
+	}
+
+	Ok(())
+}
+
+/// Check a leadership proof and return the finalized leadership of the group.
+///
+/// `pallet` is the name of pallet-archipel in the runtime. Proofs taken before `min_block`
+/// or for an epoch lower than `min_epoch` are rejected as stale.
+pub fn verify_leadership<Header: HeaderT, AccountId: Decode>(
+	pallet: &[u8],
+	group_id: u32,
+	set: &AuthoritySet,
+	proof: &LeadershipProof<Header>,
+	min_block: Header::Number,
+	min_epoch: u64,
+) -> Result<VerifiedLeadership<AccountId, Header::Number, Header::Hash>, Error> {
+	if *proof.header.number() < min_block {
+		return Err(Error::StaleProof);
+	}
+	verify_justification(&proof.header, &proof.justification, set)?;
+
+	let db = StorageProof::new(proof.proof.clone()).into_memory_db::<Header::Hashing>();
+	let root = proof.header.state_root();
+	let read = |key: &[u8]| {
+		read_trie_value::<Layout<Header::Hashing>, _>(&db, root, key).map_err(|_| Error::InvalidStorageProof)
+	};
+
+	let keys = leadership_keys(pallet, group_id);
+	let leader = read(&keys[0])?;
+	let epoch = read(&keys[1])?;
+	let leaded = read(&keys[2])?;
+
+	// Missing values are the storage defaults
+	let leaded = match leaded {
+		Some(value) => bool::decode(&mut &value[..]).map_err(|_| Error::InvalidStorageValue)?,
+		None => false,
+	};
+	let epoch = match epoch {
+		Some(value) => u64::decode(&mut &value[..]).map_err(|_| Error::InvalidStorageValue)?,
+		None => 0,
+	};
+	if epoch < min_epoch {
+		return Err(Error::StaleProof);
+	}
+	let leader = match leader {
+		Some(value) if leaded => Some(AccountId::decode(&mut &value[..]).map_err(|_| Error::InvalidStorageValue)?),
+		_ => None,
+	};
+
+	Ok(VerifiedLeadership {
+		block_number: *proof.header.number(),
+		block_hash: proof.header.hash(),
+		leader,
+		epoch,
+	})
+}
+
+/// Check a leadership proof shows `account` is the finalized leader of the group.
+pub fn ensure_leader<Header: HeaderT, AccountId: Decode + PartialEq>(
+	pallet: &[u8],
+	group_id: u32,
+	set: &AuthoritySet,
+	proof: &LeadershipProof<Header>,
+	min_block: Header::Number,
+	min_epoch: u64,
+	account: &AccountId,
+) -> Result<VerifiedLeadership<AccountId, Header::Number, Header::Hash>, Error> {
+	let leadership = verify_leadership::<Header, AccountId>(pallet, group_id, set, proof, min_block, min_epoch)?;
+	if leadership.leader.as_ref() != Some(account) {
+		return Err(Error::NotLeader);
+	}
+	Ok(leadership)
+}
@@ -0,0 +1,198 @@
+use crate::*;
+use finality_grandpa::{Precommit, SignedPrecommit};
+use sp_core::{crypto::AccountId32, H256};
+use sp_keyring::Ed25519Keyring;
+use sp_runtime::{generic, traits::BlakeTwo256};
+use sp_state_machine::{prove_read, Backend, InMemoryBackend};
+use std::collections::BTreeMap;
+
+type Header = generic::Header<u64, BlakeTwo256>;
+
+const PALLET: &[u8] = b"ArchipelModule";
+
+// Backend with leadership storage of group 1
+fn backend(leader: AccountId32, epoch: u64, leaded: bool) -> InMemoryBackend<BlakeTwo256> {
+	let keys = leadership_keys(PALLET, 1);
+	let mut storage = BTreeMap::new();
+	storage.insert(keys[0].clone(), leader.encode());
+	storage.insert(keys[1].clone(), epoch.encode());
+	storage.insert(keys[2].clone(), leaded.encode());
+	storage.into()
+}
+
+fn header(number: u64, state_root: H256) -> Header {
+	Header::new(number, Default::default(), state_root, Default::default(), Default::default())
+}
+
+fn authority_set(voters: &[Ed25519Keyring]) -> AuthoritySet {
+	AuthoritySet {
+		set_id: 0,
+		authorities: voters.iter().map(|voter| (voter.public().into(), 1)).collect(),
+	}
+}
+
+// Justification of header signed by voters
+fn justification(header: &Header, voters: &[Ed25519Keyring]) -> Vec<u8> {
+	let precommit = Precommit { target_hash: header.hash(), target_number: *header.number() };
+	let message = Message::Precommit(precommit.clone());
+	let precommits = voters
+		.iter()
+		.map(|voter| {
+			let payload = sp_finality_grandpa::localized_payload(1, 0, &message);
+			SignedPrecommit {
+				precommit: precommit.clone(),
+				signature: voter.sign(&payload[..]).into(),
+				id: voter.public().into(),
+			}
+		})
+		.collect();
+
+	GrandpaJustification::<Header> {
+		round: 1,
+		commit: Commit::<Header> { target_hash: header.hash(), target_number: *header.number(), precommits },
+		votes_ancestries: Vec::new(),
+	}
+	.encode()
+}
+
+// Leadership proof of group 1 signed by voters
+fn leadership_proof(leader: AccountId32, leaded: bool, voters: &[Ed25519Keyring]) -> LeadershipProof<Header> {
+	leadership_proof_at(1, leader, 3, leaded, voters)
+}
+
+// Leadership proof of group 1 at a given block and epoch
+fn leadership_proof_at(
+	number: u64,
+	leader: AccountId32,
+	epoch: u64,
+	leaded: bool,
+	voters: &[Ed25519Keyring],
+) -> LeadershipProof<Header> {
+	let backend = backend(leader, epoch, leaded);
+	let root = backend.storage_root(std::iter::empty()).0;
+	let proof = prove_read(backend, leadership_keys(PALLET, 1).iter()).unwrap();
+	let header = header(number, root);
+
+	LeadershipProof {
+		justification: justification(&header, voters),
+		header,
+		proof: proof.iter_nodes().collect(),
+	}
+}
+
+#[test]
+fn leadership_keys_should_match_storage_layout() {
+	let keys = leadership_keys(PALLET, 1);
+	assert_eq!(keys.len(), 3);
+	for key in keys.iter() {
+		assert_eq!(&key[..16], &sp_core::hashing::twox_128(PALLET)[..]);
+		assert_eq!(&key[key.len() - 4..], &1u32.encode()[..]);
+	}
+	assert_eq!(&keys[0][16..32], &sp_core::hashing::twox_128(b"Leaders")[..]);
+	assert_ne!(keys[0], leadership_keys(PALLET, 2)[0]);
+}
+
+#[test]
+fn leadership_proof_should_be_verified() {
+	let voters = [Ed25519Keyring::Alice, Ed25519Keyring::Bob, Ed25519Keyring::Charlie];
+	let leader: AccountId32 = sp_keyring::Sr25519Keyring::Alice.to_account_id();
+	let proof = leadership_proof(leader.clone(), true, &voters);
+
+	let leadership = verify_leadership::<Header, AccountId32>(PALLET, 1, &authority_set(&voters), &proof, 0, 0).unwrap();
+	assert_eq!(leadership.block_number, 1);
+	assert_eq!(leadership.block_hash, proof.header.hash());
+	assert_eq!(leadership.leader, Some(leader.clone()));
+	assert_eq!(leadership.epoch, 3);
+
+	assert!(ensure_leader(PALLET, 1, &authority_set(&voters), &proof, 0, 0, &leader).is_ok());
+	let other: AccountId32 = sp_keyring::Sr25519Keyring::Bob.to_account_id();
+	assert_eq!(ensure_leader(PALLET, 1, &authority_set(&voters), &proof, 0, 0, &other), Err(Error::NotLeader));
+}
+
+#[test]
+fn group_without_leader_should_be_verified() {
+	let voters = [Ed25519Keyring::Alice];
+	let proof = leadership_proof(sp_keyring::Sr25519Keyring::Alice.to_account_id(), false, &voters);
+
+	let leadership = verify_leadership::<Header, AccountId32>(PALLET, 1, &authority_set(&voters), &proof, 0, 0).unwrap();
+	assert_eq!(leadership.leader, None);
+}
+
+#[test]
+fn justification_should_reach_supermajority() {
+	let voters = [Ed25519Keyring::Alice, Ed25519Keyring::Bob, Ed25519Keyring::Charlie];
+	let leader = sp_keyring::Sr25519Keyring::Alice.to_account_id();
+
+	// 2 of 3 is not more than two thirds
+	let proof = leadership_proof(leader.clone(), true, &voters[..2]);
+	assert_eq!(
+		verify_leadership::<Header, AccountId32>(PALLET, 1, &authority_set(&voters), &proof, 0, 0),
+		Err(Error::NotEnoughWeight),
+	);
+
+	// precommits of unknown authorities are rejected
+	let proof = leadership_proof(leader.clone(), true, &[Ed25519Keyring::Dave]);
+	assert_eq!(
+		verify_leadership::<Header, AccountId32>(PALLET, 1, &authority_set(&voters), &proof, 0, 0),
+		Err(Error::UnknownAuthority),
+	);
+
+	// justification signed for another authority set is rejected
+	let proof = leadership_proof(leader, true, &voters);
+	let mut set = authority_set(&voters);
+	set.set_id = 1;
+	assert_eq!(
+		verify_leadership::<Header, AccountId32>(PALLET, 1, &set, &proof, 0, 0),
+		Err(Error::InvalidSignature),
+	);
+}
+
+#[test]
+fn forged_proof_should_fail() {
+	let voters = [Ed25519Keyring::Alice];
+	let leader = sp_keyring::Sr25519Keyring::Alice.to_account_id();
+	let set = authority_set(&voters);
+
+	// justification of another block
+	let mut proof = leadership_proof(leader.clone(), true, &voters);
+	let other = leadership_proof(sp_keyring::Sr25519Keyring::Bob.to_account_id(), true, &voters);
+	proof.justification = other.justification.clone();
+	assert_eq!(
+		verify_leadership::<Header, AccountId32>(PALLET, 1, &set, &proof, 0, 0),
+		Err(Error::JustificationTargetMismatch),
+	);
+
+	// storage proof of another state
+	let mut proof = leadership_proof(leader, true, &voters);
+	proof.proof = other.proof.clone();
+	assert_eq!(
+		verify_leadership::<Header, AccountId32>(PALLET, 1, &set, &proof, 0, 0),
+		Err(Error::InvalidStorageProof),
+	);
+
+	// undecodable justification
+	let mut proof = other;
+	proof.justification = vec![1, 2, 3];
+	assert_eq!(
+		verify_leadership::<Header, AccountId32>(PALLET, 1, &set, &proof, 0, 0),
+		Err(Error::InvalidJustification),
+	);
+}
+
+#[test]
+fn stale_proof_should_fail() {
+	let voters = [Ed25519Keyring::Alice];
+	let set = authority_set(&voters);
+	let alice = sp_keyring::Sr25519Keyring::Alice.to_account_id();
+	let bob = sp_keyring::Sr25519Keyring::Bob.to_account_id();
+
+	// Alice led at block 5 before Bob took over at block 8
+	let old = leadership_proof_at(5, alice.clone(), 3, true, &voters);
+	let new = leadership_proof_at(8, bob.clone(), 4, true, &voters);
+	assert!(ensure_leader(PALLET, 1, &set, &new, 8, 4, &bob).is_ok());
+
+	// replaying the proof of Alice is rejected once the new leadership is known
+	assert_eq!(ensure_leader(PALLET, 1, &set, &old, 8, 0, &alice), Err(Error::StaleProof));
+	assert_eq!(ensure_leader(PALLET, 1, &set, &old, 0, 4, &alice), Err(Error::StaleProof));
+	assert!(ensure_leader(PALLET, 1, &set, &old, 5, 3, &alice).is_ok());
+}
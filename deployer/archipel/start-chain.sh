@@ -237,33 +237,30 @@ mv /tmp/archipelSpecTmp.json /root/chain/archipelSpec.json
 cat /root/chain/archipelSpec.json | jq  '.id = "archipel"'  > /tmp/archipelSpecTmp.json
 mv /tmp/archipelSpecTmp.json /root/chain/archipelSpec.json
 
-# add SS58 Adress to palletAura.authorities
- cat /root/chain/archipelSpec.json | jq '.genesis.runtime.palletAura.authorities = []'  > /tmp/archipelSpecTmp.json
- mv /tmp/archipelSpecTmp.json /root/chain/archipelSpec.json
-indexArray=0
-for AUTH in $ARCHIPEL_AUTHORITIES_SR25519_LIST_CLEAN
-do
-      cat /root/chain/archipelSpec.json | jq --arg AUTH $AUTH '.genesis.runtime.palletAura.authorities += [$AUTH]'  > /tmp/archipelSpecTmp.json
-      mv /tmp/archipelSpecTmp.json /root/chain/archipelSpec.json
-      indexArray=$(( $indexArray + 1 ))
-done
+# aura and grandpa authorities are set by the session pallet from validators session keys
+cat /root/chain/archipelSpec.json | jq '.genesis.runtime.palletAura.authorities = [] | .genesis.runtime.palletGrandpa.authorities = []'  > /tmp/archipelSpecTmp.json
+mv /tmp/archipelSpecTmp.json /root/chain/archipelSpec.json
+
+# authorities lists are paired by index: Sr25519 address is the operator account, aura and archipel key, Ed25519 address its grandpa key
+ARCHIPEL_AUTHORITIES_SR25519_ARRAY=($ARCHIPEL_AUTHORITIES_SR25519_LIST_CLEAN)
+ARCHIPEL_AUTHORITIES_ED25519_ARRAY=($ARCHIPEL_AUTHORITIES_ED25519_LIST_CLEAN)
+if [ "${#ARCHIPEL_AUTHORITIES_SR25519_ARRAY[@]}" != "${#ARCHIPEL_AUTHORITIES_ED25519_ARRAY[@]}" ]
+then
+      echo "\$ARCHIPEL_AUTHORITIES_SR25519_LIST and \$ARCHIPEL_AUTHORITIES_ED25519_LIST must have the same length"
+      exit 1
+fi
 
-# add SS58 Adress to palletGrandpa.authorities
-cat /root/chain/archipelSpec.json | jq '.genesis.runtime.palletGrandpa.authorities = ["REPLACE_AUTHORITIES_HERE"]'  > /tmp/archipelSpecTmp.json
+# add SS58 Adress to palletValidatorSet.validators and their keys to palletSession.keys
+cat /root/chain/archipelSpec.json | jq '.genesis.runtime.palletValidatorSet.validators = [] | .genesis.runtime.palletSession.keys = []'  > /tmp/archipelSpecTmp.json
 mv /tmp/archipelSpecTmp.json /root/chain/archipelSpec.json
-LIST_TO_INJECT=""
-indexArray=0
-for ITEM in $ARCHIPEL_AUTHORITIES_ED25519_LIST_CLEAN
+for indexArray in "${!ARCHIPEL_AUTHORITIES_SR25519_ARRAY[@]}"
 do
-      ITEM_OUTPUT=$(echo "[\"$ITEM\",1]")
-      LIST_TO_INJECT="$LIST_TO_INJECT $ITEM_OUTPUT,"
-      indexArray=$(( $indexArray + 1 ))
+      AUTH=${ARCHIPEL_AUTHORITIES_SR25519_ARRAY[$indexArray]}
+      GRANDPA=${ARCHIPEL_AUTHORITIES_ED25519_ARRAY[$indexArray]}
+      cat /root/chain/archipelSpec.json | jq --arg AUTH $AUTH --arg GRANDPA $GRANDPA '.genesis.runtime.palletValidatorSet.validators += [$AUTH] | .genesis.runtime.palletSession.keys += [[$AUTH, $AUTH, {"aura": $AUTH, "grandpa": $GRANDPA, "archipel": $AUTH}]]'  > /tmp/archipelSpecTmp.json
+      mv /tmp/archipelSpecTmp.json /root/chain/archipelSpec.json
 done
 
-# remove last , of loop
-LIST_TO_INJECT=${LIST_TO_INJECT%?} 
-sed -i "s/\"REPLACE_AUTHORITIES_HERE\"/`echo $LIST_TO_INJECT`/g" /root/chain/archipelSpec.json
-
 # add SS58 Adress Balances 
 cat /root/chain/archipelSpec.json | jq '.genesis.runtime.palletBalances.balances = ["REPLACE_BALANCES_HERE"]'  > /tmp/archipelSpecTmp.json
 mv /tmp/archipelSpecTmp.json /root/chain/archipelSpec.json
@@ -280,6 +277,24 @@ done
 LIST_TO_INJECT=${LIST_TO_INJECT%?} 
 sed -i "s/\"REPLACE_BALANCES_HERE\"/`echo $LIST_TO_INJECT`/g" /root/chain/archipelSpec.json
 
+# add SS58 Adress to palletArchipel.members, groups and leaders are taken on chain by orchestrators
+cat /root/chain/archipelSpec.json | jq '.genesis.runtime.palletArchipel.members = [] | .genesis.runtime.palletArchipel.groups = [] | .genesis.runtime.palletArchipel.leaders = []'  > /tmp/archipelSpecTmp.json
+mv /tmp/archipelSpecTmp.json /root/chain/archipelSpec.json
+for AUTH in $ARCHIPEL_AUTHORITIES_SR25519_LIST_CLEAN
+do
+      cat /root/chain/archipelSpec.json | jq --arg AUTH $AUTH '.genesis.runtime.palletArchipel.members += [$AUTH]'  > /tmp/archipelSpecTmp.json
+      mv /tmp/archipelSpecTmp.json /root/chain/archipelSpec.json
+done
+
+# add SS58 Adress to palletMembershipInstance1.members, council members are initialized by council membership
+cat /root/chain/archipelSpec.json | jq '.genesis.runtime.palletMembershipInstance1.members = [] | .genesis.runtime.palletCollectiveInstance1.members = []'  > /tmp/archipelSpecTmp.json
+mv /tmp/archipelSpecTmp.json /root/chain/archipelSpec.json
+for AUTH in $ARCHIPEL_AUTHORITIES_SR25519_LIST_CLEAN
+do
+      cat /root/chain/archipelSpec.json | jq --arg AUTH $AUTH '.genesis.runtime.palletMembershipInstance1.members += [$AUTH]'  > /tmp/archipelSpecTmp.json
+      mv /tmp/archipelSpecTmp.json /root/chain/archipelSpec.json
+done
+
 # reserved peers list construct
 RESERVED_PEERS_PARAM="--reserved-only"
 if [ ! -z "$ARCHIPEL_RESERVED_PEERS" ]
@@ -330,15 +345,18 @@ fi
 
 ED25519_FILE_PATH=$(echo "6772616e$ED25519_WITHOUT_0X")
 SR25519_FILE_PATH=$(echo "61757261$SR25519_WITHOUT_0X")
+ARCHIPEL_FILE_PATH=$(echo "61726368$SR25519_WITHOUT_0X")
 
 if [ ! -z "$DEBUG" ]; then
       echo "ED25519_FILE_PATH: $ED25519_FILE_PATH"
       echo "SR25519_FILE_PATH: $SR25519_FILE_PATH"
+      echo "ARCHIPEL_FILE_PATH: $ARCHIPEL_FILE_PATH"
 fi
 
 echo "Writing key seed into files..."
 echo "\"$ARCHIPEL_KEY_SEED\"" > "/root/chain/data/chains/archipel/keystore/$ED25519_FILE_PATH"
 echo "\"$ARCHIPEL_KEY_SEED\"" > "/root/chain/data/chains/archipel/keystore/$SR25519_FILE_PATH"
+echo "\"$ARCHIPEL_KEY_SEED\"" > "/root/chain/data/chains/archipel/keystore/$ARCHIPEL_FILE_PATH"
 
 if [ ! -z "$DEBUG" ]; then
       echo "RESERVED_PEERS_PARAM: $RESERVED_PEERS_PARAM"
@@ -31,6 +31,9 @@ async function main () {
     // Bootstrap orchestration before orchestration
     await orchestrator.bootstrapOrchestrator();
 
+    // Bootstrap heartbeats send
+    await orchestrator.bootstrapHeartbeats();
+
     // Create chain event listener
     chain.listenEvents(heartbeats, config.mnemonic, orchestrator);
 
@@ -41,9 +44,10 @@ async function main () {
         console.log('Checking if heartbeats send is enabled...');
         if (!orchestrator.heartbeatSendEnabled || !orchestrator.heartbeatSendEnabledAdmin) {
           console.log('Heartbeat send is disabled...');
+          await orchestrator.stopHeartbeats();
           return;
         }
-        await chain.addHeartbeat(orchestrator.getServiceMode(), config.mnemonic, config.nodeGroupId);
+        await orchestrator.sendHeartbeat();
       } catch (error) {
         console.error(error);
       }
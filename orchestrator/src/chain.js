@@ -1,5 +1,7 @@
 const { ApiPromise, WsProvider } = require('@polkadot/api');
 const debug = require('debug')('chain');
+const { stringToHex } = require('@polkadot/util');
+const { mnemonicGenerate } = require('@polkadot/util-crypto');
 
 const {
   getKeysFromSeed,
@@ -16,6 +18,12 @@ class Chain {
     this.lastBlockThreshold = 10;
 
     this.finalizedGap = 10;
+
+    // Number of blocks a set leader transaction stays valid
+    this.leaderRequestValidity = 10;
+
+    // Number of entries read at once from archipel paginated rpc
+    this.pageSize = 100;
   }
 
   // Connect to chain
@@ -29,7 +37,180 @@ class Chain {
         // mapping the actual specified address format
         Address: 'MultiAddress',
         // mapping the lookup
-        LookupSource: 'MultiAddress'
+        LookupSource: 'MultiAddress',
+        // archipel node status reported in heartbeats
+        NodeStatus: {
+          _enum: ['Unknown', 'Active', 'Passive', 'Maintenance']
+        },
+        // archipel heartbeat sent unsigned by the offchain worker
+        Heartbeat: {
+          public: 'MultiSigner',
+          groupId: 'u32',
+          status: 'NodeStatus',
+          blockNumber: 'BlockNumber'
+        },
+        // archipel group registry
+        ServiceType: {
+          _enum: ['Polkadot', 'Kusama', 'Centrifuge', 'Trustlines']
+        },
+        GroupInfo: {
+          name: 'Vec<u8>',
+          service: 'ServiceType',
+          admin: 'AccountId',
+          aliveTime: 'BlockNumber',
+          leaseDuration: 'BlockNumber'
+        },
+        GroupInfoOf: 'GroupInfo',
+        // archipel node liveness inside its group
+        MemberState: {
+          lastHeartbeat: 'BlockNumber',
+          status: 'NodeStatus'
+        },
+        // archipel group state returned by archipel rpc
+        GroupMemberState: {
+          account: 'AccountId',
+          lastHeartbeat: 'BlockNumber',
+          status: 'NodeStatus',
+          alive: 'bool'
+        },
+        GroupState: {
+          blockNumber: 'BlockNumber',
+          leader: 'Option<AccountId>',
+          epoch: 'u64',
+          leaseEnd: 'Option<BlockNumber>',
+          members: 'Vec<GroupMemberState>'
+        },
+        // archipel leadership at best and finalized blocks returned by archipel rpc
+        Leadership: {
+          blockNumber: 'BlockNumber',
+          leader: 'Option<AccountId>',
+          epoch: 'u64',
+          leaseEnd: 'Option<BlockNumber>'
+        },
+        LeadershipView: {
+          bestHash: 'Hash',
+          best: 'Leadership',
+          finalizedHash: 'Hash',
+          finalized: 'Leadership',
+          isFinal: 'bool'
+        },
+        // archipel leadership proof at a block finalized by grandpa
+        LeadershipProofResponse: {
+          header: 'Header',
+          justification: 'Bytes',
+          proof: 'Vec<Bytes>'
+        },
+        // archipel recommended action returned by archipel rpc
+        RecommendedAction: {
+          _enum: ['BecomeLeader', 'StayActive', 'StayPassive', 'GiveUp']
+        },
+        ActionReason: {
+          _enum: ['NotMember', 'NodeNotAlive', 'Maintenance', 'NoOtherMemberAlive', 'NoLeader', 'IsLeader', 'LeaderAlive', 'LeaderNotAlive']
+        },
+        Recommendation: {
+          action: 'RecommendedAction',
+          reason: 'ActionReason',
+          leader: 'Option<AccountId>',
+          epoch: 'u64'
+        },
+        // archipel group events streamed by archipel rpc subscription
+        GroupEvent: {
+          _enum: {
+            NewHeartbeat: {
+              account: 'AccountId',
+              status: 'NodeStatus',
+              blockNumber: 'BlockNumber'
+            },
+            NewLeader: {
+              account: 'AccountId',
+              epoch: 'u64'
+            },
+            GiveUpLeader: {
+              account: 'AccountId',
+              epoch: 'u64'
+            }
+          }
+        },
+        NotificationOrigin: {
+          _enum: ['Best', 'Finalized']
+        },
+        GroupEventsNotification: {
+          blockHash: 'Hash',
+          origin: 'NotificationOrigin',
+          events: 'Vec<GroupEvent>'
+        }
+      },
+      rpc: {
+        archipel: {
+          groupState: {
+            description: 'Leadership and members liveness of a group read at one block',
+            params: [
+              { name: 'groupId', type: 'u32' },
+              { name: 'at', type: 'Hash', isOptional: true }
+            ],
+            type: 'GroupState'
+          },
+          leadership: {
+            description: 'Leadership of a group at best and finalized blocks',
+            params: [
+              { name: 'groupId', type: 'u32' }
+            ],
+            type: 'LeadershipView'
+          },
+          leadershipProof: {
+            description: 'Proof of a group leadership at the last block finalized with a known GRANDPA justification',
+            params: [
+              { name: 'groupId', type: 'u32' }
+            ],
+            type: 'LeadershipProofResponse'
+          },
+          recommendedAction: {
+            description: 'Action a node should take for its service in a group, with its reason',
+            params: [
+              { name: 'account', type: 'AccountId' },
+              { name: 'groupId', type: 'u32' },
+              { name: 'at', type: 'Hash', isOptional: true }
+            ],
+            type: 'Recommendation'
+          },
+          groupMembers: {
+            description: 'Members of a group with their liveness, at most limit members following startingAfter',
+            params: [
+              { name: 'groupId', type: 'u32' },
+              { name: 'startingAfter', type: 'Option<AccountId>' },
+              { name: 'limit', type: 'u32' },
+              { name: 'at', type: 'Hash', isOptional: true }
+            ],
+            type: 'Vec<GroupMemberState>'
+          },
+          nodeGroups: {
+            description: 'Groups a node sent heartbeats for, at most limit groups following startingAfter',
+            params: [
+              { name: 'account', type: 'AccountId' },
+              { name: 'startingAfter', type: 'Option<u32>' },
+              { name: 'limit', type: 'u32' },
+              { name: 'at', type: 'Hash', isOptional: true }
+            ],
+            type: 'Vec<u32>'
+          },
+          registeredGroups: {
+            description: 'Registered groups, at most limit groups following startingAfter',
+            params: [
+              { name: 'startingAfter', type: 'Option<u32>' },
+              { name: 'limit', type: 'u32' },
+              { name: 'at', type: 'Hash', isOptional: true }
+            ],
+            type: 'Vec<(u32, GroupInfo)>'
+          },
+          subscribeGroupEvents: {
+            description: 'Subscribe to heartbeats and leader changes of a group',
+            params: [
+              { name: 'groupId', type: 'u32' }
+            ],
+            pubsub: ['groupEvents', 'subscribeGroupEvents', 'unsubscribeGroupEvents'],
+            type: 'GroupEventsNotification'
+          }
+        }
       }
     });
     this.provider = provider;
@@ -49,29 +230,153 @@ class Chain {
   // Listen events
   async listenEvents (heartbeats, mnemonic, orchestrator) {
     const keys = await getKeysFromSeed(mnemonic);
-    // Subscribe to events
-    await this.api.query.system.events((events) => {
+    // Subscribe to events of orchestrator group
+    await this.api.rpc.archipel.subscribeGroupEvents(orchestrator.group, (notification) => {
+      // Leader changes are notified again once finalized
+      if (notification.origin.isFinalized) {
+        debug('listenEvents', `Leader change finalized in block ${notification.blockHash.toString()}`);
+        return;
+      }
       // Loop through events
-      events.forEach(({ event }) => {
+      notification.events.forEach((event) => {
         // If change leader event received
-        if (event.section.toString() === 'archipelModule' && event.method.toString() === 'NewLeader') {
-          debug('listenEvents', `Received new leader event from ${event.data[0].toString()}`);
+        if (event.isNewLeader) {
+          debug('listenEvents', `Received new leader event from ${event.asNewLeader.account.toString()}`);
           debug('listenEvents', JSON.stringify(event));
           // If anyone other took leadership
-          if (event.data[0].toString() !== keys.address.toString() && event.data[1].toString() === orchestrator.group.toString()) {
+          if (event.asNewLeader.account.toString() !== keys.address.toString()) {
             orchestrator.serviceStart('passive');
           }
         }
         // Add heartbeat if NewHeartbeat event was received
-        if (event.section.toString() === 'archipelModule' && event.method.toString() === 'NewHeartbeat') {
-          debug('listenEvents', `Received NewHeartbeat event from ${event.data[0].toString()}`);
+        if (event.isNewHeartbeat) {
+          const { account, status, blockNumber } = event.asNewHeartbeat;
+          debug('listenEvents', `Received NewHeartbeat event from ${account.toString()}`);
           debug('listenEvents', JSON.stringify(event));
-          heartbeats.addHeartbeat(event.data[0].toString(), event.data[1].toString(), event.data[2].toString(), event.data[3].toString());
+          heartbeats.addHeartbeat(account.toString(), orchestrator.group.toString(), status.index.toString(), blockNumber.toString());
         }
       });
     });
   }
 
+  // Insert node archipel key used by offchain worker to sign heartbeats
+  async insertArchipelKey (mnemonic) {
+    try {
+      const keys = await getKeysFromSeed(mnemonic);
+      await this.api.rpc.author.insertKey('arch', mnemonic, keys.publicKey);
+      return true;
+    } catch (error) {
+      debug('insertArchipelKey', error);
+      return false;
+    }
+  }
+
+  // Insert a new archipel key in node keystore and register it for the member account
+  async rotateArchipelKey (mnemonic, archipelMnemonic = mnemonicGenerate()) {
+    // If node state permits to send transactions
+    const sendTransaction = await this.canSendTransactions();
+    // If node has any peers and is not in synchronizing chain
+    if (sendTransaction) {
+      // Get keys from mnemonic
+      const keys = await getKeysFromSeed(mnemonic);
+
+      // Offchain worker signs heartbeats with the new archipel key
+      const archipelKeys = await getKeysFromSeed(archipelMnemonic);
+      if (!(await this.insertArchipelKey(archipelMnemonic))) {
+        return false;
+      }
+
+      // Archipel key proves its ownership by signing member account and keys nonce
+      const keysNonce = await this.api.query.archipelModule.keysNonce(keys.address);
+      const archipelKey = { Sr25519: archipelKeys.publicKey };
+      const proof = this.api.createType('(MultiSigner, AccountId, u32)', [archipelKey, keys.address, keysNonce]);
+      const signature = { Sr25519: archipelKeys.sign(proof.toU8a()) };
+
+      // Get account nonce
+      const accountNonce = await this.api.query.system.account(keys.address);
+      const nonce = accountNonce.nonce;
+
+      return new Promise((resolve, reject) => {
+        // create, sign and send transaction
+        this.api.tx.archipelModule
+          // create transaction
+          .setKeys(archipelKey, signature)
+          // Sign and transaction
+          .sign(keys, { nonce })
+          // Send transaction
+          .send(({ events = [], status }) => {
+            // Debug show transaction status
+            debug('rotateArchipelKey', transactionGetStatus(status));
+            if (status.isFinalized) {
+              events.forEach(async ({ event: { method, section } }) => {
+                if (section.toString() === 'archipelModule' && method.toString() === 'KeysSet') {
+                  debug('rotateArchipelKey', 'Archipel key was successfully set.');
+                  resolve(true);
+                }
+              });
+              resolve(false);
+            }
+            // If transaction is not ok resolving promise to false
+            if (status.isDropped || status.isInvalid || status.isUsurped) {
+              resolve(false);
+            }
+          }).catch(err => reject(err));
+      });
+    } else {
+      console.log('Archipel node can\'t recieve transactions...');
+      return false;
+    }
+  }
+
+  // Get archipel key set by a member
+  async getArchipelKey (key) {
+    try {
+      const archipelKey = await this.api.query.archipelModule.keys(key);
+      return archipelKey.isSome ? archipelKey.unwrap() : undefined;
+    } catch (error) {
+      debug('getArchipelKey', error);
+      throw error;
+    }
+  }
+
+  // Check if node keystore holds the archipel key set by a member
+  async hasArchipelKey (key) {
+    try {
+      const archipelKey = await this.getArchipelKey(key);
+      return archipelKey !== undefined && (await this.api.rpc.author.hasKey(archipelKey.asSr25519, 'arch')).isTrue;
+    } catch (error) {
+      debug('hasArchipelKey', error);
+      return false;
+    }
+  }
+
+  // Set group and status sent in heartbeats by offchain worker
+  async setOffchainHeartbeat (mode, nodeGroupId) {
+    try {
+      const nodeStatus = await fromModeToNodeStatus(mode);
+      const group = this.api.createType('u32', nodeGroupId).toHex();
+      const status = this.api.createType('NodeStatus', nodeStatus).toHex();
+      await this.api.rpc.offchain.localStorageSet('PERSISTENT', stringToHex('archipel::group'), group);
+      await this.api.rpc.offchain.localStorageSet('PERSISTENT', stringToHex('archipel::status'), status);
+      return true;
+    } catch (error) {
+      debug('setOffchainHeartbeat', error);
+      return false;
+    }
+  }
+
+  // Stop heartbeats of offchain worker, empty group and status can't be decoded by it
+  async clearOffchainHeartbeat () {
+    try {
+      await this.api.rpc.offchain.localStorageSet('PERSISTENT', stringToHex('archipel::group'), '0x');
+      await this.api.rpc.offchain.localStorageSet('PERSISTENT', stringToHex('archipel::status'), '0x');
+      return true;
+    } catch (error) {
+      debug('clearOffchainHeartbeat', error);
+      return false;
+    }
+  }
+
   // Send heartbeat
   async addHeartbeat (mode, mnemonic, nodeGroupId) {
     // If node state permits to send transactions
@@ -137,13 +442,17 @@ class Chain {
       const accountNonce = await this.api.query.system.account(keys.address);
       const nonce = accountNonce.nonce;
 
+      // Get leadership epoch observed by this node and transaction validity
+      const epoch = await this.getLeadershipEpoch(groupId);
+      const validUntil = await this.getBestNumber() + this.leaderRequestValidity;
+
       // Nonce show
-      debug('setLeader', `Nonce: ${nonce}`);
+      debug('setLeader', `Nonce: ${nonce} epoch ${epoch} validUntil ${validUntil}`);
       return new Promise((resolve, reject) => {
         // create, sign and send transaction
         this.api.tx.archipelModule
           // create transaction
-          .setLeader(oldLeader, groupId)
+          .setLeader(oldLeader, groupId, epoch, validUntil)
           // Sign and transaction
           .sign(keys, { nonce })
           // Send transaction
@@ -296,6 +605,16 @@ class Chain {
     }
   }
 
+  // Get leadership epoch from Runtime
+  async getLeadershipEpoch (groupId) {
+    try {
+      return parseInt((await this.api.query.archipelModule.leadershipEpochs(groupId)).toString());
+    } catch (error) {
+      debug('getLeadershipEpoch', error);
+      return 0;
+    }
+  }
+
   // Get leadedGroup status from Runtime
   async isLeadedGroup (groupId) {
     try {
@@ -316,23 +635,130 @@ class Chain {
     }
   }
 
-  // Get Node Status from Runtime
-  async getNodeStatus (key) {
+  // Get leader, epoch and members liveness of a group read at one block
+  async getGroupState (groupId) {
+    try {
+      return await this.api.rpc.archipel.groupState(groupId);
+    } catch (error) {
+      debug('getGroupState', error);
+      return false;
+    }
+  }
+
+  // Get leadership of a group at best and finalized blocks
+  async getLeadership (groupId) {
+    try {
+      return await this.api.rpc.archipel.leadership(groupId);
+    } catch (error) {
+      debug('getLeadership', error);
+      return false;
+    }
+  }
+
+  // Check if key leadership of a group is finalized and can't be reverted by a reorg
+  async isLeadershipFinal (key, groupId) {
+    const leadership = await this.getLeadership(groupId);
+    if (!leadership) {
+      return false;
+    }
+    const finalizedLeader = leadership.finalized.leader;
+    return leadership.isFinal.isTrue && finalizedLeader.isSome && finalizedLeader.unwrap().toString() === key.toString();
+  }
+
+  // Get action recommended by chain to a node for its service in a group
+  async getRecommendedAction (key, groupId) {
     try {
-      return parseInt((await this.api.query.archipelModule.nodesStatus(key)).toString());
+      return await this.api.rpc.archipel.recommendedAction(key, groupId);
+    } catch (error) {
+      debug('getRecommendedAction', error);
+      return false;
+    }
+  }
+
+  // Check if node joined a group with a heartbeat
+  async isGroupMember (key, groupId) {
+    try {
+      const state = await this.api.query.archipelModule.groupMembers(groupId, key);
+      return state.isSome;
+    } catch (error) {
+      debug('isGroupMember', error);
+      return false;
+    }
+  }
+
+  // Get Node Status in a group from Runtime
+  async getNodeStatus (key, groupId) {
+    try {
+      const state = await this.api.query.archipelModule.groupMembers(groupId, key);
+      return state.isSome ? state.unwrap().status.index : 0;
     } catch (error) {
       debug('getNodeStatus', error);
       return 0;
     }
   }
 
-  // Get Node Group from Runtime
-  async getNodeGroup (key) {
+  // Read all pages of an archipel paginated rpc, each page starting after the cursor of the previous one
+  async readPages (readPage, cursorOf) {
+    const entries = [];
+    let startingAfter = null;
+    for (;;) {
+      const page = await readPage(startingAfter, this.pageSize);
+      entries.push(...page);
+      if (page.length < this.pageSize) {
+        return entries;
+      }
+      startingAfter = cursorOf(page[page.length - 1]);
+    }
+  }
+
+  // Get Node Groups from Runtime
+  async getNodeGroups (key) {
     try {
-      return parseInt((await this.api.query.archipelModule.groups(key)).toString());
+      const groups = await this.readPages(
+        (startingAfter, limit) => this.api.rpc.archipel.nodeGroups(key, startingAfter, limit),
+        group => group
+      );
+      return groups.map(group => parseInt(group.toString()));
     } catch (error) {
-      debug('getNodeGroup', error);
-      return 0;
+      debug('getNodeGroups', error);
+      return [];
+    }
+  }
+
+  // Get members of a group with their last heartbeat, status and liveness from Runtime
+  async getGroupMembers (groupId) {
+    try {
+      const members = await this.readPages(
+        (startingAfter, limit) => this.api.rpc.archipel.groupMembers(groupId, startingAfter, limit),
+        member => member.account
+      );
+      return members.map(member => ({
+        wallet: member.account.toString(),
+        blockNumber: parseInt(member.lastHeartbeat.toString()),
+        nodeStatus: member.status.index,
+        alive: member.alive.isTrue
+      }));
+    } catch (error) {
+      debug('getGroupMembers', error);
+      return [];
+    }
+  }
+
+  // Get registered groups with their parameters from Runtime
+  async getRegisteredGroups () {
+    try {
+      const groups = await this.readPages(
+        (startingAfter, limit) => this.api.rpc.archipel.registeredGroups(startingAfter, limit),
+        ([groupId]) => groupId
+      );
+      return groups.map(([groupId, info]) => ({
+        groupId: parseInt(groupId.toString()),
+        aliveTime: parseInt(info.aliveTime.toString()),
+        leaseDuration: parseInt(info.leaseDuration.toString())
+      }));
+    } catch (error) {
+      debug('getRegisteredGroups', error);
+      return [];
     }
   }
 
@@ -33,6 +33,9 @@ class Orchestrator {
     this.heartbeatSendEnabled = true;
     this.heartbeatSendEnabledAdmin = config.heartbeatEnabled;
 
+    // Heartbeats are sent by node offchain worker once its archipel key is set
+    this.offchainHeartbeats = false;
+
     // If service is not ready and is in active node counters
     this.noReadyCount = 0;
     this.noReadyThreshold = 30; // ~ 300 seconds
@@ -70,12 +73,61 @@ class Orchestrator {
     await this.fillHeartbeatsFromChain();
   }
 
-  // This function fills heartbeat values from chain for every wallet from node wallets
+  // Set archipel key of node so its offchain worker sends heartbeats instead of orchestrator transactions
+  async bootstrapHeartbeats () {
+    const key = await getKeysFromSeed(this.mnemonic);
+    this.offchainHeartbeats = await this.chain.hasArchipelKey(key.address);
+    if (!this.offchainHeartbeats) {
+      console.log('Setting archipel key of node...');
+      this.offchainHeartbeats = await this.chain.rotateArchipelKey(this.mnemonic);
+    }
+    console.log(this.offchainHeartbeats
+      ? 'Heartbeats will be sent by node offchain worker...'
+      : 'Archipel key is not set. Heartbeats will be sent by orchestrator...');
+  }
+
+  // Send heartbeat with current service mode, offchain worker heartbeats are only accepted once node joined its group
+  async sendHeartbeat () {
+    const key = await getKeysFromSeed(this.mnemonic);
+    if (this.offchainHeartbeats && await this.chain.isGroupMember(key.address, this.group)) {
+      return await this.chain.setOffchainHeartbeat(this.getServiceMode(), this.group);
+    }
+    return await this.chain.addHeartbeat(this.getServiceMode(), this.mnemonic, this.group);
+  }
+
+  // Stop heartbeats sent by node offchain worker
+  async stopHeartbeats () {
+    if (this.offchainHeartbeats) {
+      return await this.chain.clearOffchainHeartbeat();
+    }
+    return false;
+  }
+
+  // This function fills heartbeat values for every wallet from node wallets and group members known by chain
   async fillHeartbeatsFromChain () {
     const walletList = this.nodesWallets.toString().split(',');
     for (const wallet of walletList) {
-      const heartbeatBlock = await this.chain.getHeartbeat(wallet);
-      this.heartbeats.addHeartbeat(wallet, 0, 0, heartbeatBlock);
+      this.heartbeats.addHeartbeat(wallet, 0, 0, 0);
+    }
+    await this.updateHeartbeatsFromChain();
+  }
+
+  // This function updates heartbeat values with the last heartbeats of group members read from chain
+  async updateHeartbeatsFromChain () {
+    const members = await this.chain.getGroupMembers(this.group);
+    for (const member of members) {
+      this.heartbeats.addHeartbeat(member.wallet, this.group, member.nodeStatus, member.blockNumber);
+    }
+  }
+
+  // This function updates heartbeat values with group members liveness read by chain at one block
+  async updateHeartbeatsFromGroupState () {
+    const groupState = await this.chain.getGroupState(this.group);
+    if (!groupState) {
+      return;
+    }
+    for (const member of groupState.members) {
+      this.heartbeats.addHeartbeat(member.account.toString(), this.group, member.status.index, member.lastHeartbeat.toString());
     }
   }
 
@@ -179,8 +231,18 @@ class Orchestrator {
     const bestNumber = await this.chain.getBestNumber();
     debug('orchestrateService', `bestNumber: ${bestNumber}`);
 
-    // Check if anyone is alive
+    // Every orchestrator follows the action chain computes with the same rules from federation state
+    console.log('Getting recommended action from chain...');
+    const recommendation = await this.chain.getRecommendedAction(nodeKey, this.group);
+    if (recommendation) {
+      await this.followRecommendation(nodeKey, recommendation);
+      return;
+    }
+    console.log('Chain can\'t recommend an action. Orchestrating with heartbeats...');
+
+    // Check if anyone is alive with group members heartbeats read from chain
     console.log('Checking is anyone in federation is alive...');
+    await this.updateHeartbeatsFromChain();
     if (!this.heartbeats.anyOneAlive(nodeKey, this.aliveTime, this.group, bestNumber)) {
       console.log(
         "Seems that no one is alive or current node doesn't send heartbeats. Enforcing 'passive' service mode..."
@@ -216,11 +278,70 @@ class Orchestrator {
       return;
     }
 
+    await this.activateFinalizedLeader(nodeKey);
+  }
+
+  // Act as recommended by chain
+  async followRecommendation (nodeKey, recommendation) {
+    const { action, reason } = recommendation;
+    console.log(`Chain recommends ${action.toString()} because of ${reason.toString()}...`);
+
+    // Keeping known heartbeats up to date for orchestrator info
+    await this.updateHeartbeatsFromGroupState();
+
+    if (action.isStayPassive) {
+      await this.serviceStart('passive');
+      return;
+    }
+
+    if (action.isGiveUp) {
+      console.log('Giving up leadership...');
+      await this.chain.giveUpLeadership(this.group, this.mnemonic);
+      await this.serviceStart('passive');
+      return;
+    }
+
+    // Chain can't know if the local service is ready or if heartbeats send was disabled
+    console.log('Checking if service is ready to start...');
+    const serviceReady = await this.serviceReadinessManagement();
+    if (!serviceReady) {
+      console.log("Service is not ready. Enforcing 'passive' service mode...");
+      await this.serviceStart('passive');
+      return;
+    }
+    if (!this.heartbeatSendEnabled || !this.heartbeatSendEnabledAdmin) {
+      console.log('Heartbeat send is disabled. Enforcing passive service mode...');
+      await this.serviceStart('passive');
+      return;
+    }
+
+    if (action.isBecomeLeader) {
+      // The old leader is given to chain to replace it, or the node itself if group is not leaded
+      const currentLeader = recommendation.leader.isSome ? recommendation.leader.unwrap().toString() : nodeKey;
+      const becomeLeaderResult = await this.becomeLeader(currentLeader);
+      if (!becomeLeaderResult) {
+        console.log("Leadership was not taken. Enforcing 'passive' service mode...");
+        await this.serviceStart('passive');
+        return;
+      }
+    }
+
+    await this.activateFinalizedLeader(nodeKey);
+  }
+
+  // Launch service in active mode if current node is the finalized leader
+  async activateFinalizedLeader (nodeKey) {
     // We will recheck if after all checks the current node is leader
     // If so we will launch service in active mode
     const currentLeader = await this.chain.getLeader(this.group);
 
     if (currentLeader.toString() === nodeKey) {
+      // Leadership taken in a block that can still be reverted must not start service in active mode
+      const isLeadershipFinal = await this.chain.isLeadershipFinal(nodeKey, this.group);
+      if (!isLeadershipFinal) {
+        console.log('Current node is leader but leadership is not finalized yet. Waiting for finality...');
+        return;
+      }
       console.log('All checks passed and current node is leader. Launching service in active mode...');
       await this.serviceStart('active');
     }
@@ -4,7 +4,7 @@ const { assert } = require('chai');
 const { Chain } = require('../src/chain');
 const { getKeysFromSeed, constructNodesList } = require('../src/utils');
 const { Heartbeats } = require('../src/heartbeats');
-const { assertReturn } = require('@polkadot/util');
+const { assertReturn, stringToHex } = require('@polkadot/util');
 
 // Test configuration
 let chain;
@@ -218,11 +218,35 @@ describe('Archipel chain test', function () {
     const heartbeat = await chain.getHeartbeat(keys.address.toString());
     assert.isAbove(heartbeat, 0, 'check if heartbeat was added');
 
-    const nodeStatus = await chain.getNodeStatus(keys.address.toString());
+    const nodeStatus = await chain.getNodeStatus(keys.address.toString(), 1);
     assert.equal(nodeStatus, 1, 'cheack node status');
 
-    const nodeGroup = await chain.getNodeGroup(keys.address.toString());
-    assert.equal(nodeGroup, 1, 'check node group');
+    const nodeGroups = await chain.getNodeGroups(keys.address.toString());
+    assert.deepEqual(nodeGroups, [1], 'check node groups');
+  });
+
+  it('Test paginated group members and node groups', async () => {
+    const keys1 = await getKeysFromSeed(mnemonic1);
+    const keys2 = await getKeysFromSeed(mnemonic2);
+    let result = await chain.addHeartbeat('passive', mnemonic1, '45');
+    assert.equal(result, true, 'check if heartbeat add transaction was executed 1');
+    result = await chain.addHeartbeat('active', mnemonic2, '45');
+    assert.equal(result, true, 'check if heartbeat add transaction was executed 2');
+
+    // Reading one entry per page to go through cursors
+    const savePageSize = chain.pageSize;
+    chain.pageSize = 1;
+
+    const members = await chain.getGroupMembers(45);
+    const wallets = members.map(member => member.wallet).sort();
+    assert.deepEqual(wallets, [keys1.address.toString(), keys2.address.toString()].sort(), 'check group members read page by page');
+    assert.equal(members.every(member => member.alive), true, 'check if group members are alive');
+    assert.equal(members.find(member => member.wallet === keys2.address.toString()).nodeStatus, 1, 'check group member status');
+
+    const nodeGroups = await chain.getNodeGroups(keys1.address.toString());
+    assert.deepEqual(nodeGroups.sort(), [1, 45], 'check node groups read page by page');
+
+    chain.pageSize = savePageSize;
   });
 
   it('Test leader set', async () => {
@@ -237,6 +261,57 @@ describe('Archipel chain test', function () {
     assert.equal(statusGiveUp, true, 'check if give up leadership transaction was executed');
   });
 
+  it('Test leadership finality', async () => {
+    const keys = await getKeysFromSeed(mnemonic1);
+    const status = await chain.setLeader(keys.address, 44, mnemonic1);
+    assert.equal(status, true, 'check if leader set transaction was executed');
+
+    // Leader set transaction resolves once finalized
+    const isFinal = await chain.isLeadershipFinal(keys.address, 44);
+    assert.equal(isFinal, true, 'check if leadership is finalized');
+
+    const keys2 = await getKeysFromSeed(mnemonic2);
+    const isFinalOther = await chain.isLeadershipFinal(keys2.address, 44);
+    assert.equal(isFinalOther, false, 'check if leadership of other node is not finalized');
+
+    const statusGiveUp = await chain.giveUpLeadership(44, mnemonic1);
+    assert.equal(statusGiveUp, true, 'check if give up leadership transaction was executed');
+  });
+
+  it('Test archipel key rotation', async () => {
+    const keys = await getKeysFromSeed(mnemonic1);
+    const noKeyYet = await chain.getArchipelKey(keys.address);
+    assert.equal(noKeyYet, undefined, 'check if archipel key is not set before rotation');
+
+    const status = await chain.rotateArchipelKey(mnemonic1);
+    assert.equal(status, true, 'check if set keys transaction was executed');
+
+    const archipelKey = await chain.getArchipelKey(keys.address);
+    assert.equal(archipelKey.isSr25519, true, 'check if archipel key is set after rotation');
+  });
+
+  it('Test offchain heartbeat group and status', async () => {
+    const keys = await getKeysFromSeed(mnemonic1);
+    const hasKey = await chain.hasArchipelKey(keys.address);
+    assert.equal(hasKey, true, 'check if node keystore holds the rotated archipel key');
+
+    const keys2 = await getKeysFromSeed(mnemonic2);
+    const hasKeyOther = await chain.hasArchipelKey(keys2.address);
+    assert.equal(hasKeyOther, false, 'check if member without archipel key is detected');
+
+    const readOffchain = async key => (await chain.api.rpc.offchain.localStorageGet('PERSISTENT', stringToHex(key))).unwrap().toHex();
+
+    const status = await chain.setOffchainHeartbeat('passive', 1);
+    assert.equal(status, true, 'check if offchain heartbeat was set');
+    assert.equal(await readOffchain('archipel::group'), chain.api.createType('u32', 1).toHex(), 'check if offchain group is set');
+    assert.equal(await readOffchain('archipel::status'), chain.api.createType('NodeStatus', 2).toHex(), 'check if offchain status is set');
+
+    const cleared = await chain.clearOffchainHeartbeat();
+    assert.equal(cleared, true, 'check if offchain heartbeat was cleared');
+    assert.equal(await readOffchain('archipel::group'), '0x', 'check if offchain group is cleared');
+    assert.equal(await readOffchain('archipel::status'), '0x', 'check if offchain status is cleared');
+  });
+
   it('Test LeadedGroup - no leader set', async () => {
     const groupIsNotLeaded = await chain.isLeadedGroup(42);
     assert.equal(groupIsNotLeaded, false, 'check is a group is not leaded at the begining');
@@ -452,8 +527,10 @@ describe('Archipel chain test', function () {
     const keys = await getKeysFromSeed(mnemonic1);
 
     assert.equal(await chain.getHeartbeat(keys.address), 0, 'Trying to get heartbeat while not connected to chain');
-    assert.equal(await chain.getNodeStatus(keys.address), 0, 'Trying to get node status while not connected to chain');
-    assert.equal(await chain.getNodeGroup(keys.address), 0, 'Trying to get node group while not connected to chain');
+    assert.equal(await chain.getNodeStatus(keys.address, 1), 0, 'Trying to get node status while not connected to chain');
+    assert.deepEqual(await chain.getNodeGroups(keys.address), [], 'Trying to get node groups while not connected to chain');
+    assert.deepEqual(await chain.getGroupMembers(1), [], 'Trying to get group members while not connected to chain');
+    assert.deepEqual(await chain.getRegisteredGroups(), [], 'Trying to get registered groups while not connected to chain');
     assert.equal(await chain.getBestNumber(), 0, 'Trying to getbest number while not connected to chain');
     assert.equal(await chain.getBestNumberFinalized(), 0, 'Trying to getbestfinalized number while not connected to chain');
     assert.equal(await chain.getPeerNumber(), 0, 'Trying to get peer number while not connected to chain')
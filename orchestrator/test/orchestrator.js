@@ -1,6 +1,7 @@
 /* eslint-disable */
 const { exec } = require('child_process');
 const { assert } = require('chai');
+const sinon = require('sinon');
 
 const { Orchestrator } = require('../src/orchestrator');
 const { Docker } = require('../src/docker');
@@ -95,6 +96,9 @@ describe('Orchestrator test', function () {
       chain,
       heartbeats);
 
+    // Decisions are taken from the heartbeats set by each test instead of chain recommendations
+    orchestrator.chain.getRecommendedAction = async () => false;
+
     // Mock isServiceReadyToStart method of service
     orchestrator.service.serviceInstance.isServiceReadyToStart = () => true;
     orchestrator.service.serviceInstance.importKey = () => true;
@@ -123,9 +127,9 @@ describe('Orchestrator test', function () {
   });
 
   it('Test service bootstrap before orchestration', async function () {
-    const saveChainGetHeartbeat = orchestrator.chain.getHeartbeat;
+    const saveChainGetGroupMembers = orchestrator.chain.getGroupMembers;
 
-    orchestrator.chain.getHeartbeat = async () => 0;
+    orchestrator.chain.getGroupMembers = async () => [];
       
     await orchestrator.bootstrapOrchestrator();
 
@@ -164,6 +168,8 @@ describe('Orchestrator test', function () {
     result = await chain.addHeartbeat('active', mnemonic2, '1');
     assert.equal(result, true, 'check if heartbeat add transaction was executed');
 
+    // Group members heartbeats are read from chain
+    orchestrator.chain.getGroupMembers = saveChainGetGroupMembers;
     await orchestrator.bootstrapOrchestrator();
 
     const heartbeatBlock1 = await chain.getHeartbeat('5FmqMTGCW6yGmqzu2Mp9f7kLgyi5NfLmYPWDVMNw9UqwU2Bs');
@@ -172,15 +178,15 @@ describe('Orchestrator test', function () {
       {
         wallet: '5FmqMTGCW6yGmqzu2Mp9f7kLgyi5NfLmYPWDVMNw9UqwU2Bs',
         name: 'test-archipel-NODE-1',
-        group: 0,
-        nodeStatus: 0,
+        group: 1,
+        nodeStatus: 1,
         blockNumber: heartbeatBlock1
       },
       {
         wallet: '5H19p4jm177Aj4X28xwL2cAAbxgyAcitZU5ox8hHteScvsex',
         name: 'test-archipel-NODE-2',
-        group: 0,
-        nodeStatus: 0,
+        group: 1,
+        nodeStatus: 1,
         blockNumber: heartbeatBlock2
       },
       {
@@ -193,7 +199,6 @@ describe('Orchestrator test', function () {
     ];
     assert.equal(JSON.stringify(orchestrator.heartbeats.getAllHeartbeats()), JSON.stringify(hearbeatsNeedBe), 'Check if heartbeats where correctly initialized with some heartbeats added onchain');
 
-    orchestrator.chain.getHeartbeat = saveChainGetHeartbeat;
     const saveOrchestratorServiceStart = orchestrator.serviceStart;
     orchestrator.serviceStart = async () => false;
 
@@ -485,9 +490,9 @@ describe('Orchestrator test', function () {
   });
 
   it('Test if no one is alive the service remains is passive mode', async function () {
-    const saveChainGetHeartbeat = orchestrator.chain.getHeartbeat;
+    const saveChainGetGroupMembers = orchestrator.chain.getGroupMembers;
 
-    orchestrator.chain.getHeartbeat = async () => 0;
+    orchestrator.chain.getGroupMembers = async () => [];
 
     const heartbeats = new Heartbeats(config.nodesWallets, config.archipelName);
   
@@ -511,13 +516,13 @@ describe('Orchestrator test', function () {
     await orchestrator.serviceCleanUp();
 
     orchestrator.heartbeats = saveHeartbeats;
-    orchestrator.chain.getHeartbeat = saveChainGetHeartbeat;
+    orchestrator.chain.getGroupMembers = saveChainGetGroupMembers;
   });
 
   it('Check if service is not ready to start the service remains in passive mode', async function () {
-    const saveChainGetHeartbeat = orchestrator.chain.getHeartbeat;
+    const saveChainGetGroupMembers = orchestrator.chain.getGroupMembers;
 
-    orchestrator.chain.getHeartbeat = async () => 0;
+    orchestrator.chain.getGroupMembers = async () => [];
 
     const heartbeats = new Heartbeats(config.nodesWallets, config.archipelName);
   
@@ -554,13 +559,13 @@ describe('Orchestrator test', function () {
 
     orchestrator.heartbeats = saveHeartbeats;
     orchestrator.mnemonic = saveMnemonic;
-    orchestrator.chain.getHeartbeat = saveChainGetHeartbeat;
+    orchestrator.chain.getGroupMembers = saveChainGetGroupMembers;
   });
 
   it('Test if chain can not receive transactions the service remains in passive mode', async function () {
-    const saveChainGetHeartbeat = orchestrator.chain.getHeartbeat;
+    const saveChainGetGroupMembers = orchestrator.chain.getGroupMembers;
 
-    orchestrator.chain.getHeartbeat = async () => 0;
+    orchestrator.chain.getGroupMembers = async () => [];
     
     const heartbeats = new Heartbeats(config.nodesWallets, config.archipelName);
   
@@ -593,14 +598,14 @@ describe('Orchestrator test', function () {
     
     await orchestrator.serviceCleanUp();
 
-    orchestrator.chain.getHeartbeat = saveChainGetHeartbeat;
+    orchestrator.chain.getGroupMembers = saveChainGetGroupMembers;
     orchestrator.chain.canSendTransactions = canSendTransactionsSave;
   });
 
   it('Test if no leader and someone and current node are alive take leadership and start service in active mode', async function () {
-    const saveChainGetHeartbeat = orchestrator.chain.getHeartbeat;
+    const saveChainGetGroupMembers = orchestrator.chain.getGroupMembers;
 
-    orchestrator.chain.getHeartbeat = async () => 0;
+    orchestrator.chain.getGroupMembers = async () => [];
 
     const heartbeats = new Heartbeats(config.nodesWallets, config.archipelName);
   
@@ -638,15 +643,15 @@ describe('Orchestrator test', function () {
     assert.equal(status, true, 'check if give up leadership transaction was executed');
     await orchestrator.serviceCleanUp();
 
-    orchestrator.chain.getHeartbeat = saveChainGetHeartbeat;
+    orchestrator.chain.getGroupMembers = saveChainGetGroupMembers;
     orchestrator.heartbeats = saveHeartbeats;
     orchestrator.mnemonic = saveMnemonic;
   });
 
   it('Test if leadership take transaction was executed and orchestrator must start active service but really leadership was no taken on chain', async function () {
-    const saveChainGetHeartbeat = orchestrator.chain.getHeartbeat;
+    const saveChainGetGroupMembers = orchestrator.chain.getGroupMembers;
 
-    orchestrator.chain.getHeartbeat = async () => 0;
+    orchestrator.chain.getGroupMembers = async () => [];
 
     const heartbeats = new Heartbeats(config.nodesWallets, config.archipelName);
   
@@ -683,16 +688,16 @@ describe('Orchestrator test', function () {
     console.log('Cleanup...');
     await orchestrator.serviceCleanUp();
 
-    orchestrator.chain.getHeartbeat = saveChainGetHeartbeat;
+    orchestrator.chain.getGroupMembers = saveChainGetGroupMembers;
     orchestrator.leadershipManagement = saveLeadershipManagement;
     orchestrator.heartbeats = saveHeartbeats;
     orchestrator.mnemonic = saveMnemonic;
   });
 
   it('Test if no leader and someone and current node are alive but in another group get leadership on your group and start service in active mode', async function () {
-    const saveChainGetHeartbeat = orchestrator.chain.getHeartbeat;
+    const saveChainGetGroupMembers = orchestrator.chain.getGroupMembers;
 
-    orchestrator.chain.getHeartbeat = async () => 0;
+    orchestrator.chain.getGroupMembers = async () => [];
 
     const heartbeats = new Heartbeats(config.nodesWallets, config.archipelName);
   
@@ -730,15 +735,15 @@ describe('Orchestrator test', function () {
     assert.equal(status, true, 'check if give up leadership transaction was executed');
     await orchestrator.serviceCleanUp();
 
-    orchestrator.chain.getHeartbeat = saveChainGetHeartbeat;
+    orchestrator.chain.getGroupMembers = saveChainGetGroupMembers;
     orchestrator.heartbeats = saveHeartbeats;
     orchestrator.mnemonic = saveMnemonic;
   });
 
   it('Test if I am leader and someone and current node are alive the service remains in active mode', async function () {
-    const saveChainGetHeartbeat = orchestrator.chain.getHeartbeat;
+    const saveChainGetGroupMembers = orchestrator.chain.getGroupMembers;
 
-    orchestrator.chain.getHeartbeat = async () => 0;
+    orchestrator.chain.getGroupMembers = async () => [];
 
     const heartbeats = new Heartbeats(config.nodesWallets, config.archipelName);
   
@@ -783,15 +788,15 @@ describe('Orchestrator test', function () {
     assert.equal(statusGl, true, 'check if give up leadership transaction was executed');
     await orchestrator.serviceCleanUp();
 
-    orchestrator.chain.getHeartbeat = saveChainGetHeartbeat;
+    orchestrator.chain.getGroupMembers = saveChainGetGroupMembers;
     orchestrator.heartbeats = saveHeartbeats;
     orchestrator.mnemonic = saveMnemonic;
   });
 
   it('If other node is leader and nobody is alive no leadership change and service remains in passive mode', async function () {
-    const saveChainGetHeartbeat = orchestrator.chain.getHeartbeat;
+    const saveChainGetGroupMembers = orchestrator.chain.getGroupMembers;
 
-    orchestrator.chain.getHeartbeat = async () => 0;
+    orchestrator.chain.getGroupMembers = async () => [];
 
     const heartbeats = new Heartbeats(config.nodesWallets, config.archipelName);
   
@@ -824,15 +829,15 @@ describe('Orchestrator test', function () {
     assert.equal(statusGiveUp, true, 'check if give up leadership transaction was executed');
     await orchestrator.serviceCleanUp();
 
-    orchestrator.chain.getHeartbeat = saveChainGetHeartbeat;
+    orchestrator.chain.getGroupMembers = saveChainGetGroupMembers;
     orchestrator.heartbeats = saveHeartbeats;
     orchestrator.mnemonic = saveMnemonic;
   });
 
   it('If other node is leader and only current node is is alive no leadership change and service remains in passive mode', async function () {
-    const saveChainGetHeartbeat = orchestrator.chain.getHeartbeat;
+    const saveChainGetGroupMembers = orchestrator.chain.getGroupMembers;
 
-    orchestrator.chain.getHeartbeat = async () => 0;
+    orchestrator.chain.getGroupMembers = async () => [];
 
     const heartbeats = new Heartbeats(config.nodesWallets, config.archipelName);
   
@@ -869,15 +874,15 @@ describe('Orchestrator test', function () {
     assert.equal(statusGiveUp, true, 'check if give up leadership transaction was executed');
     await orchestrator.serviceCleanUp();
 
-    orchestrator.chain.getHeartbeat = saveChainGetHeartbeat;
+    orchestrator.chain.getGroupMembers = saveChainGetGroupMembers;
     orchestrator.heartbeats = saveHeartbeats;
     orchestrator.mnemonic = saveMnemonic;
   });
 
   it('If other node is leader and only other node is is alive no leadership change and service remains in passive mode', async function () {
-    const saveChainGetHeartbeat = orchestrator.chain.getHeartbeat;
+    const saveChainGetGroupMembers = orchestrator.chain.getGroupMembers;
 
-    orchestrator.chain.getHeartbeat = async () => 0;
+    orchestrator.chain.getGroupMembers = async () => [];
 
     const heartbeats = new Heartbeats(config.nodesWallets, config.archipelName);
   
@@ -914,15 +919,15 @@ describe('Orchestrator test', function () {
     assert.equal(statusGiveUp, true, 'check if give up leadership transaction was executed');
     await orchestrator.serviceCleanUp();
 
-    orchestrator.chain.getHeartbeat = saveChainGetHeartbeat;
+    orchestrator.chain.getGroupMembers = saveChainGetGroupMembers;
     orchestrator.heartbeats = saveHeartbeats;
     orchestrator.mnemonic = saveMnemonic;
   });
 
   it('If other node is leader and leader and other node is is alive no leadership change and service remains in passive mode', async function () {
-    const saveChainGetHeartbeat = orchestrator.chain.getHeartbeat;
+    const saveChainGetGroupMembers = orchestrator.chain.getGroupMembers;
 
-    orchestrator.chain.getHeartbeat = async () => 0;
+    orchestrator.chain.getGroupMembers = async () => [];
 
     const heartbeats = new Heartbeats(config.nodesWallets, config.archipelName);
   
@@ -960,15 +965,15 @@ describe('Orchestrator test', function () {
     assert.equal(statusGiveUp, true, 'check if give up leadership transaction was executed');
     await orchestrator.serviceCleanUp();
 
-    orchestrator.chain.getHeartbeat = saveChainGetHeartbeat;
+    orchestrator.chain.getGroupMembers = saveChainGetGroupMembers;
     orchestrator.heartbeats = saveHeartbeats;
     orchestrator.mnemonic = saveMnemonic;
   });
 
   it('Test if other node is leader, is offline and someone other and current node are alive try to get leadership and launch service in active mode', async function () {
-    const saveChainGetHeartbeat = orchestrator.chain.getHeartbeat;
+    const saveChainGetGroupMembers = orchestrator.chain.getGroupMembers;
 
-    orchestrator.chain.getHeartbeat = async () => 0;
+    orchestrator.chain.getGroupMembers = async () => [];
 
     const heartbeats = new Heartbeats(config.nodesWallets, config.archipelName);
   
@@ -1010,16 +1015,16 @@ describe('Orchestrator test', function () {
     assert.equal(statusGiveUp, true, 'check if give up leadership transaction was executed');
     await orchestrator.serviceCleanUp();
 
-    orchestrator.chain.getHeartbeat = saveChainGetHeartbeat;
+    orchestrator.chain.getGroupMembers = saveChainGetGroupMembers;
     orchestrator.heartbeats = saveHeartbeats;
     orchestrator.mnemonic = saveMnemonic;
     orchestrator.noLivenessThreshold = saveNoLivenessThreshold;
   });
 
   it('If other node is leader, is offline and nobody is alive do not get leadership and service remains in passive mode', async function () {
-    const saveChainGetHeartbeat = orchestrator.chain.getHeartbeat;
+    const saveChainGetGroupMembers = orchestrator.chain.getGroupMembers;
 
-    orchestrator.chain.getHeartbeat = async () => 0;
+    orchestrator.chain.getGroupMembers = async () => [];
 
     const heartbeats = new Heartbeats(config.nodesWallets, config.archipelName);
   
@@ -1066,7 +1071,7 @@ describe('Orchestrator test', function () {
     assert.equal(statusGiveUp, true, 'check if give up leadership transaction was executed');
     await orchestrator.serviceCleanUp();
 
-    orchestrator.chain.getHeartbeat = saveChainGetHeartbeat;
+    orchestrator.chain.getGroupMembers = saveChainGetGroupMembers;
     orchestrator.heartbeats = saveHeartbeats;
     orchestrator.mnemonic = saveMnemonic;
     orchestrator.aliveTime = saveAliveTime;
@@ -1074,9 +1079,9 @@ describe('Orchestrator test', function () {
   });
 
   it('If other node is leader, is offline and only current node is alive do not get leadership and service remains in passive mode', async function () {
-    const saveChainGetHeartbeat = orchestrator.chain.getHeartbeat;
+    const saveChainGetGroupMembers = orchestrator.chain.getGroupMembers;
 
-    orchestrator.chain.getHeartbeat = async () => 0;
+    orchestrator.chain.getGroupMembers = async () => [];
 
     const heartbeats = new Heartbeats(config.nodesWallets, config.archipelName);
   
@@ -1123,7 +1128,7 @@ describe('Orchestrator test', function () {
     assert.equal(statusGiveUp, true, 'check if give up leadership transaction was executed');
     await orchestrator.serviceCleanUp();
 
-    orchestrator.chain.getHeartbeat = saveChainGetHeartbeat;
+    orchestrator.chain.getGroupMembers = saveChainGetGroupMembers;
     orchestrator.heartbeats = saveHeartbeats;
     orchestrator.mnemonic = saveMnemonic;
     orchestrator.aliveTime = saveAliveTime;
@@ -1131,9 +1136,9 @@ describe('Orchestrator test', function () {
   });
 
   it('If other node is leader, is offline and only other node is alive do not get leadership and service remains in passive mode', async function () {
-    const saveChainGetHeartbeat = orchestrator.chain.getHeartbeat;
+    const saveChainGetGroupMembers = orchestrator.chain.getGroupMembers;
 
-    orchestrator.chain.getHeartbeat = async () => 0;
+    orchestrator.chain.getGroupMembers = async () => [];
 
     const heartbeats = new Heartbeats(config.nodesWallets, config.archipelName);
   
@@ -1180,7 +1185,7 @@ describe('Orchestrator test', function () {
     assert.equal(statusGiveUp, true, 'check if give up leadership transaction was executed');
     await orchestrator.serviceCleanUp();
 
-    orchestrator.chain.getHeartbeat = saveChainGetHeartbeat;
+    orchestrator.chain.getGroupMembers = saveChainGetGroupMembers;
     orchestrator.heartbeats = saveHeartbeats;
     orchestrator.mnemonic = saveMnemonic;
     orchestrator.aliveTime = saveAliveTime;
@@ -1188,9 +1193,9 @@ describe('Orchestrator test', function () {
   });
 
   it('If other node is leader is alive and no node is alive do not get leadership and service remains in passive mode', async function () {
-    const saveChainGetHeartbeat = orchestrator.chain.getHeartbeat;
+    const saveChainGetGroupMembers = orchestrator.chain.getGroupMembers;
 
-    orchestrator.chain.getHeartbeat = async () => 0;
+    orchestrator.chain.getGroupMembers = async () => [];
 
     const heartbeats = new Heartbeats(config.nodesWallets, config.archipelName);
   
@@ -1237,7 +1242,7 @@ describe('Orchestrator test', function () {
     assert.equal(statusGiveUp, true, 'check if give up leadership transaction was executed');
     await orchestrator.serviceCleanUp();
 
-    orchestrator.chain.getHeartbeat = saveChainGetHeartbeat;
+    orchestrator.chain.getGroupMembers = saveChainGetGroupMembers;
     orchestrator.heartbeats = saveHeartbeats;
     orchestrator.mnemonic = saveMnemonic;
     orchestrator.aliveTime = saveAliveTime;
@@ -1246,9 +1251,9 @@ describe('Orchestrator test', function () {
 
   
   it('If other node is leader, is offline and someone other and current node are alive test threshold', async function () {
-    const saveChainGetHeartbeat = orchestrator.chain.getHeartbeat;
+    const saveChainGetGroupMembers = orchestrator.chain.getGroupMembers;
 
-    orchestrator.chain.getHeartbeat = async () => 0;
+    orchestrator.chain.getGroupMembers = async () => [];
 
     const heartbeats = new Heartbeats(config.nodesWallets, config.archipelName);
   
@@ -1316,16 +1321,16 @@ describe('Orchestrator test', function () {
     assert.equal(statusGiveUp, true, 'check if give up leadership transaction was executed');
     await orchestrator.serviceCleanUp();
 
-    orchestrator.chain.getHeartbeat = saveChainGetHeartbeat;
+    orchestrator.chain.getGroupMembers = saveChainGetGroupMembers;
     orchestrator.noLivenessThreshold = saveNoLivenessThreshold;
     orchestrator.heartbeats = saveHeartbeats;
     orchestrator.mnemonic = saveMnemonic;
   });
 
   it('Test if other node is leader and is alive. Staying in passive mode...', async function () {
-    const saveChainGetHeartbeat = orchestrator.chain.getHeartbeat;
+    const saveChainGetGroupMembers = orchestrator.chain.getGroupMembers;
 
-    orchestrator.chain.getHeartbeat = async () => 0;
+    orchestrator.chain.getGroupMembers = async () => [];
 
     const keys1 = await getKeysFromSeed(mnemonic1);
     const keys2 = await getKeysFromSeed(mnemonic2);
@@ -1365,14 +1370,14 @@ describe('Orchestrator test', function () {
     assert.equal(statusGiveUp, true, 'check if give up leadership transaction was executed');
     await orchestrator.serviceCleanUp();
 
-    orchestrator.chain.getHeartbeat = saveChainGetHeartbeat;
+    orchestrator.chain.getGroupMembers = saveChainGetGroupMembers;
     orchestrator.heartbeats = saveHeartbeats;
   });
 
   it('Test serviceReadinessManagement', async function () {
-    const saveChainGetHeartbeat = orchestrator.chain.getHeartbeat;
+    const saveChainGetGroupMembers = orchestrator.chain.getGroupMembers;
 
-    orchestrator.chain.getHeartbeat = async () => 0;
+    orchestrator.chain.getGroupMembers = async () => [];
 
     const heartbeats = new Heartbeats(config.nodesWallets, config.archipelName);
   
@@ -1478,7 +1483,7 @@ describe('Orchestrator test', function () {
     assert.equal(statusGiveUp, true, 'check if give up leadership transaction was executed');
     await orchestrator.serviceCleanUp();
 
-    orchestrator.chain.getHeartbeat = saveChainGetHeartbeat;
+    orchestrator.chain.getGroupMembers = saveChainGetGroupMembers;
     orchestrator.noReadyThreshold = saveNoReadyThreshold;
     orchestrator.aliveTime = saveAliveTime;
     orchestrator.noLivenessThreshold = saveNoLivenessThreshold;
@@ -1487,9 +1492,9 @@ describe('Orchestrator test', function () {
   });
 
   it('Test force passive services mode if hearbeats send is disabled', async function () {
-    const saveChainGetHeartbeat = orchestrator.chain.getHeartbeat;
+    const saveChainGetGroupMembers = orchestrator.chain.getGroupMembers;
 
-    orchestrator.chain.getHeartbeat = async () => 0;
+    orchestrator.chain.getGroupMembers = async () => [];
 
     let containerName = `${process.env.POLKADOT_PREFIX}polkadot-validator`;
 
@@ -1539,15 +1544,15 @@ describe('Orchestrator test', function () {
     container = await docker.getContainer(containerName);
     assert.equal(container.description.State.Running, true, 'check if service container remains in passive mode');
     
-    orchestrator.chain.getHeartbeat = saveChainGetHeartbeat;
+    orchestrator.chain.getGroupMembers = saveChainGetGroupMembers;
     orchestrator.heartbeats = saveHeartbeatsInstrance;
     orchestrator.heartbeatSendEnabledAdmin = saveHeartbeatSendEnabledAdmin;
   });
 
   it('Other node is leader and his hearbeat was long time ago', async function () {
-    const saveChainGetHeartbeat = orchestrator.chain.getHeartbeat;
+    const saveChainGetGroupMembers = orchestrator.chain.getGroupMembers;
 
-    orchestrator.chain.getHeartbeat = async () => 0;
+    orchestrator.chain.getGroupMembers = async () => [];
 
     let isLeadedGroupTrue = await chain.isLeadedGroup(1);
     assert.equal(isLeadedGroupTrue, false, 'check if the group is not leaded');
@@ -1604,16 +1609,16 @@ describe('Orchestrator test', function () {
     assert.equal(statusGiveUp, true, 'check if give up leadership transaction was executed');
     await orchestrator.serviceCleanUp();
 
-    orchestrator.chain.getHeartbeat = saveChainGetHeartbeat;
+    orchestrator.chain.getGroupMembers = saveChainGetGroupMembers;
     orchestrator.heartbeats = saveHeartbeats;
     orchestrator.aliveTime = saveAliveTime;
     orchestrator.mnemonic = saveMnemonic;
   });
 
   it('Try to orchestrate when orchestration is disabled', async function () {
-    const saveChainGetHeartbeat = orchestrator.chain.getHeartbeat;
+    const saveChainGetGroupMembers = orchestrator.chain.getGroupMembers;
 
-    orchestrator.chain.getHeartbeat = async () => 0;
+    orchestrator.chain.getGroupMembers = async () => [];
 
     let isLeadedGroup = await chain.isLeadedGroup(1);
     assert.equal(isLeadedGroup, false, 'check if the group is not leaded');
@@ -1651,16 +1656,16 @@ describe('Orchestrator test', function () {
 
     await orchestrator.serviceCleanUp();
 
-    orchestrator.chain.getHeartbeat = saveChainGetHeartbeat;
+    orchestrator.chain.getGroupMembers = saveChainGetGroupMembers;
     orchestrator.heartbeats = saveHeartbeats;
     orchestrator.mnemonic = saveMnemonic;
     orchestrator.orchestrationEnabled = saveorchestrationEnabled;
   });
 
   it('Set leadership failed on chain', async function () {
-    const saveChainGetHeartbeat = orchestrator.chain.getHeartbeat;
+    const saveChainGetGroupMembers = orchestrator.chain.getGroupMembers;
 
-    orchestrator.chain.getHeartbeat = async () => 0;
+    orchestrator.chain.getGroupMembers = async () => [];
 
     let isLeadedGroup = await chain.isLeadedGroup(1);
     assert.equal(isLeadedGroup, false, 'check if the group is not leaded');
@@ -1698,7 +1703,7 @@ describe('Orchestrator test', function () {
 
     await orchestrator.serviceCleanUp();
 
-    orchestrator.chain.getHeartbeat = saveChainGetHeartbeat;
+    orchestrator.chain.getGroupMembers = saveChainGetGroupMembers;
     orchestrator.heartbeats = saveHeartbeats;
     orchestrator.mnemonic = saveMnemonic;
     orchestrator.chain.setLeader = saveChainSetLeader;
@@ -1706,9 +1711,9 @@ describe('Orchestrator test', function () {
   
 
   it('Test orchestration with bad service mode', async function () {
-    const saveChainGetHeartbeat = orchestrator.chain.getHeartbeat;
+    const saveChainGetGroupMembers = orchestrator.chain.getGroupMembers;
 
-    orchestrator.chain.getHeartbeat = async () => 0;
+    orchestrator.chain.getGroupMembers = async () => [];
 
     let isLeadedGroup = await chain.isLeadedGroup(1);
     assert.equal(isLeadedGroup, false, 'check if the group is not leaded');
@@ -1750,7 +1755,7 @@ describe('Orchestrator test', function () {
 
     await orchestrator.serviceCleanUp();
 
-    orchestrator.chain.getHeartbeat = saveChainGetHeartbeat;
+    orchestrator.chain.getGroupMembers = saveChainGetGroupMembers;
     orchestrator.heartbeats = saveHeartbeats;
     orchestrator.mnemonic = saveMnemonic;
     orchestrator.serviceMode = saveServiceMode; 
@@ -1759,9 +1764,9 @@ describe('Orchestrator test', function () {
   
 
   it('Test orchestrator with polkadot service and config file', async function () {
-    const saveChainGetHeartbeat = orchestrator.chain.getHeartbeat;
+    const saveChainGetGroupMembers = orchestrator.chain.getGroupMembers;
 
-    orchestrator.chain.getHeartbeat = async () => 0;
+    orchestrator.chain.getGroupMembers = async () => [];
 
     // Set env variables
     process.env.CONFIG_FILE = 'true';
@@ -1812,16 +1817,16 @@ describe('Orchestrator test', function () {
     delete process.env.CONFIG_FILE;
     delete process.env.CONFIG_FILE_PATH;
     delete process.env.NODE_ID;
-    orchestrator.chain.getHeartbeat = saveChainGetHeartbeat;
+    orchestrator.chain.getGroupMembers = saveChainGetGroupMembers;
     await orchestratorNew.serviceCleanUp();
   });
 
   it('Test get orchestrator info', async () => {
     await orchestrator.serviceCleanUp();
 
-    const saveChainGetHeartbeat = orchestrator.chain.getHeartbeat;
+    const saveChainGetGroupMembers = orchestrator.chain.getGroupMembers;
 
-    orchestrator.chain.getHeartbeat = async () => 0;
+    orchestrator.chain.getGroupMembers = async () => [];
 
     // Mock some functions to get predictable result
     const saveChainBestNumber = chain.getBestNumber;
@@ -1903,7 +1908,7 @@ describe('Orchestrator test', function () {
     // Test get service mode function
     assert.equal(orchestrator.getServiceMode(), 'none', 'check if service mode get returns a correct result');
 
-    orchestrator.chain.getHeartbeat = saveChainGetHeartbeat;
+    orchestrator.chain.getGroupMembers = saveChainGetGroupMembers;
     chain.getBestNumber = saveChainBestNumber;
     chain.getBestNumberFinalized = saveChainBestNumberFinalized;
     chain.getPeerNumber = saveGetPeerNumber;
@@ -2106,4 +2111,194 @@ describe('Orchestrator no service test', function () {
     let result = await orchestrator.forceActive();
     assert.equal(result, false, 'check if force active returns false cause no service node and there is nothing to activate or launch');
   });
-});
\ No newline at end of file
+});
+describe('Orchestrator recommended action test', function () {
+  const config = {
+    nodeRole: 'noservice',
+    heartbeatEnabled: true,
+    orchestrationEnabled: true,
+    mnemonic: mnemonic1,
+    nodeGroupId: 1,
+    nodesWallets: nodesWallets,
+    archipelName: archipelName,
+    aliveTime: 12
+  };
+
+  let nodeKey;
+  let otherKey;
+
+  // Codec like recommendation as returned by archipel_recommendedAction
+  const recommendation = (action, leader) => ({
+    action: {
+      isBecomeLeader: action === 'BecomeLeader',
+      isStayActive: action === 'StayActive',
+      isStayPassive: action === 'StayPassive',
+      isGiveUp: action === 'GiveUp',
+      toString: () => action
+    },
+    reason: { toString: () => 'Test' },
+    leader: leader ? { isSome: true, unwrap: () => leader } : { isSome: false },
+    epoch: 1
+  });
+
+  // Orchestrator with mocked chain and service
+  const mockOrchestrator = (action, leader, chainLeader, isFinal = true) => {
+    const chain = {
+      canSendTransactions: async () => true,
+      getBestNumber: async () => 10,
+      getRecommendedAction: sinon.fake.resolves(recommendation(action, leader)),
+      getGroupState: async () => ({
+        members: [{ account: otherKey, status: { index: 2 }, lastHeartbeat: 9 }]
+      }),
+      getLeader: async () => chainLeader,
+      isLeadershipFinal: sinon.fake.resolves(isFinal),
+      giveUpLeadership: sinon.fake.resolves(true)
+    };
+    const orchestrator = new Orchestrator(config, chain, new Heartbeats(nodesWallets, archipelName));
+    orchestrator.nodeRole = 'operator';
+    orchestrator.serviceMode = 'orchestrator';
+    orchestrator.service = {
+      mode: 'none',
+      serviceReady: async () => true,
+      serviceStart: sinon.fake(async mode => { orchestrator.service.mode = mode; return true; })
+    };
+    orchestrator.becomeLeader = sinon.fake.resolves(true);
+    return orchestrator;
+  };
+
+  before(async () => {
+    nodeKey = (await getKeysFromSeed(mnemonic1)).address;
+    otherKey = (await getKeysFromSeed(mnemonic2)).address;
+  });
+
+  it('Test recommended become leader takes leadership and starts active service once final', async () => {
+    const orchestrator = mockOrchestrator('BecomeLeader', otherKey, nodeKey);
+    await orchestrator.orchestrateService();
+
+    sinon.assert.calledWith(orchestrator.chain.getRecommendedAction, nodeKey, 1);
+    sinon.assert.calledWith(orchestrator.becomeLeader, otherKey);
+    sinon.assert.calledWith(orchestrator.chain.isLeadershipFinal, nodeKey, 1);
+    assert.equal(orchestrator.getServiceMode(), 'active', 'check if active service was started');
+    assert.equal(orchestrator.heartbeats.getHeartbeat(otherKey).blockNumber, 9, 'check if heartbeats were read from group state');
+  });
+
+  it('Test recommended become leader in a group without leader replaces current node', async () => {
+    const orchestrator = mockOrchestrator('BecomeLeader', undefined, nodeKey);
+    await orchestrator.orchestrateService();
+
+    sinon.assert.calledWith(orchestrator.becomeLeader, nodeKey);
+    assert.equal(orchestrator.getServiceMode(), 'active', 'check if active service was started');
+  });
+
+  it('Test recommended stay active waits for leadership finality', async () => {
+    const orchestrator = mockOrchestrator('StayActive', nodeKey, nodeKey, false);
+    await orchestrator.orchestrateService();
+
+    sinon.assert.notCalled(orchestrator.becomeLeader);
+    sinon.assert.notCalled(orchestrator.service.serviceStart);
+    assert.equal(orchestrator.getServiceMode(), 'none', 'check if service mode is unchanged until leadership is final');
+  });
+
+  it('Test recommended stay active is not followed if service is not ready', async () => {
+    const orchestrator = mockOrchestrator('StayActive', nodeKey, nodeKey);
+    orchestrator.service.serviceReady = async () => false;
+    await orchestrator.orchestrateService();
+
+    sinon.assert.notCalled(orchestrator.chain.isLeadershipFinal);
+    assert.equal(orchestrator.getServiceMode(), 'passive', 'check if passive service was started');
+  });
+
+  it('Test recommended stay passive starts passive service', async () => {
+    const orchestrator = mockOrchestrator('StayPassive', otherKey, otherKey);
+    await orchestrator.orchestrateService();
+
+    sinon.assert.notCalled(orchestrator.becomeLeader);
+    sinon.assert.notCalled(orchestrator.chain.giveUpLeadership);
+    assert.equal(orchestrator.getServiceMode(), 'passive', 'check if passive service was started');
+  });
+
+  it('Test recommended give up leaves leadership and starts passive service', async () => {
+    const orchestrator = mockOrchestrator('GiveUp', nodeKey, nodeKey);
+    await orchestrator.orchestrateService();
+
+    sinon.assert.calledWith(orchestrator.chain.giveUpLeadership, 1, mnemonic1);
+    assert.equal(orchestrator.getServiceMode(), 'passive', 'check if passive service was started');
+  });
+});
+
+describe('Orchestrator offchain heartbeats test', function () {
+  const config = {
+    nodeRole: 'noservice',
+    heartbeatEnabled: true,
+    orchestrationEnabled: true,
+    mnemonic: mnemonic1,
+    nodeGroupId: 1,
+    nodesWallets: nodesWallets,
+    archipelName: archipelName,
+    aliveTime: 12
+  };
+
+  // Orchestrator with mocked heartbeat chain calls
+  const mockOrchestrator = (hasArchipelKey, rotated, isGroupMember = true) => {
+    const chain = {
+      hasArchipelKey: sinon.fake.resolves(hasArchipelKey),
+      rotateArchipelKey: sinon.fake.resolves(rotated),
+      isGroupMember: sinon.fake.resolves(isGroupMember),
+      setOffchainHeartbeat: sinon.fake.resolves(true),
+      clearOffchainHeartbeat: sinon.fake.resolves(true),
+      addHeartbeat: sinon.fake.resolves(true)
+    };
+    return new Orchestrator(config, chain, new Heartbeats(nodesWallets, archipelName));
+  };
+
+  it('Test heartbeats are sent by offchain worker if node holds its archipel key', async () => {
+    const orchestrator = mockOrchestrator(true, false);
+    await orchestrator.bootstrapHeartbeats();
+
+    const keys = await getKeysFromSeed(mnemonic1);
+    sinon.assert.calledWith(orchestrator.chain.hasArchipelKey, keys.address);
+    sinon.assert.notCalled(orchestrator.chain.rotateArchipelKey);
+    assert.equal(orchestrator.offchainHeartbeats, true, 'check if offchain heartbeats are used');
+
+    await orchestrator.sendHeartbeat();
+    sinon.assert.calledWith(orchestrator.chain.setOffchainHeartbeat, 'noservice', 1);
+    sinon.assert.notCalled(orchestrator.chain.addHeartbeat);
+
+    await orchestrator.stopHeartbeats();
+    sinon.assert.calledOnce(orchestrator.chain.clearOffchainHeartbeat);
+  });
+
+  it('Test node joins its group with a transaction before offchain worker heartbeats', async () => {
+    const orchestrator = mockOrchestrator(true, false, false);
+    await orchestrator.bootstrapHeartbeats();
+    assert.equal(orchestrator.offchainHeartbeats, true, 'check if offchain heartbeats are used');
+
+    await orchestrator.sendHeartbeat();
+    const keys = await getKeysFromSeed(mnemonic1);
+    sinon.assert.calledWith(orchestrator.chain.isGroupMember, keys.address, 1);
+    sinon.assert.calledWith(orchestrator.chain.addHeartbeat, 'noservice', mnemonic1, 1);
+    sinon.assert.notCalled(orchestrator.chain.setOffchainHeartbeat);
+  });
+
+  it('Test archipel key is set at bootstrap if node does not hold it', async () => {
+    const orchestrator = mockOrchestrator(false, true);
+    await orchestrator.bootstrapHeartbeats();
+
+    sinon.assert.calledWith(orchestrator.chain.rotateArchipelKey, mnemonic1);
+    assert.equal(orchestrator.offchainHeartbeats, true, 'check if offchain heartbeats are used once key is set');
+  });
+
+  it('Test heartbeats are sent with transactions if archipel key can not be set', async () => {
+    const orchestrator = mockOrchestrator(false, false);
+    await orchestrator.bootstrapHeartbeats();
+    assert.equal(orchestrator.offchainHeartbeats, false, 'check if offchain heartbeats are not used');
+
+    await orchestrator.sendHeartbeat();
+    sinon.assert.calledWith(orchestrator.chain.addHeartbeat, 'noservice', mnemonic1, 1);
+    sinon.assert.notCalled(orchestrator.chain.setOffchainHeartbeat);
+
+    const stopped = await orchestrator.stopHeartbeats();
+    assert.equal(stopped, false, 'check if nothing is stopped without offchain heartbeats');
+    sinon.assert.notCalled(orchestrator.chain.clearOffchainHeartbeat);
+  });
+});